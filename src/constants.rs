@@ -1,27 +1,73 @@
 use sdl2::keyboard::Keycode;
 use sdl2::pixels::Color;
 
+// the size of the world in logical units, the window starts at this size and is
+// scaled to it, with black bars to keep the ratio
 pub const WINDOW_HEIGHT: u32 = 720;
 pub const WINDOW_WIDTH: u32 = WINDOW_HEIGHT * 16 / 9;
 pub const FPS: u64 = 60;
 pub const POINT_TO_WIN: u32 = 10;
+pub const LIVES: u32 = 5;
+pub const MAX_BALLS: usize = 8;
+pub const MAX_PICKUPS: usize = 2;
+pub const PICKUP_SPAWN: u32 = 6 * FPS as u32;
+pub const STICKY_HOLD: u32 = FPS as u32 / 2;
+pub const SERVE_TIMEOUT: u64 = 3;
+pub const IDLE_TIMEOUT: u64 = 30;
+pub const NET_DELAY: u32 = 3;
+pub const NET_TIMEOUT: u64 = 10;
+pub const SPECTATOR_DELAY: u64 = 500;
+/// bytes not sent yet to a spectator before it is dropped
+pub const SPECTATOR_BACKLOG: usize = 1 << 20;
+pub const PAUSE_WAIT: u32 = 250;
+pub const TERMINAL_KEY_HOLD: u32 = FPS as u32 / 4;
+pub const TERMINAL_FRAME_SKIP: u32 = 2;
+pub const SQUASH_BEST_FILE: &str = ".pong_squash_best";
+pub const SCREENSHOT_FILE: &str = "pong";
+pub const AUDIO_RATE: u32 = 44100;
+pub const BEEP_MS: u32 = 120;
+pub const BEEP_VOLUME: i16 = 4000;
+pub const COUNTDOWN_BEEP: u32 = 440;
+pub const COUNTDOWN_GO_BEEP: u32 = 880;
 
 pub const RESET: Keycode = Keycode::R;
 pub const QUIT: Keycode = Keycode::Escape;
 pub const PAUSE: Keycode = Keycode::P;
+pub const DEBUG: Keycode = Keycode::F3;
+pub const SCREENSHOT: Keycode = Keycode::F12;
+pub const FULLSCREEN: Keycode = Keycode::F11;
 
 pub const PADDLE_L_UP: Keycode = Keycode::W;
 pub const PADDLE_L_DOWN: Keycode = Keycode::S;
 pub const PADDLE_R_UP: Keycode = Keycode::K;
 pub const PADDLE_R_DOWN: Keycode = Keycode::J;
+pub const PADDLE_LF_UP: Keycode = Keycode::E;
+pub const PADDLE_LF_DOWN: Keycode = Keycode::D;
+pub const PADDLE_RF_UP: Keycode = Keycode::Up;
+pub const PADDLE_RF_DOWN: Keycode = Keycode::Down;
+pub const PADDLE_T_LEFT: Keycode = Keycode::C;
+pub const PADDLE_T_RIGHT: Keycode = Keycode::V;
+pub const PADDLE_B_LEFT: Keycode = Keycode::Left;
+pub const PADDLE_B_RIGHT: Keycode = Keycode::Right;
+pub const SERVE_L: Keycode = Keycode::A;
+pub const SERVE_R: Keycode = Keycode::L;
 
 pub const PADDLE_L_X: i32 = (WINDOW_WIDTH / 16 - PADDLE_WIDTH / 2) as i32;
 pub const PADDLE_R_X: i32 = ((WINDOW_WIDTH - WINDOW_WIDTH / 16) - PADDLE_WIDTH / 2) as i32;
+pub const PADDLE_LF_X: i32 = (WINDOW_WIDTH * 3 / 8 - PADDLE_WIDTH / 2) as i32;
+pub const PADDLE_RF_X: i32 = (WINDOW_WIDTH * 5 / 8 - PADDLE_WIDTH / 2) as i32;
 pub const PADDLE_L_HEIGHT: u32 = WINDOW_HEIGHT / 8;
 pub const PADDLE_R_HEIGHT: u32 = WINDOW_HEIGHT / 8;
 pub const PADDLE_WIDTH: u32 = WINDOW_WIDTH / 64;
 pub const PADDLE_L_STEP: u32 = PADDLE_L_HEIGHT / 8;
 pub const PADDLE_R_STEP: u32 = PADDLE_R_HEIGHT / 8;
+pub const PADDLE_T_Y: i32 = (WINDOW_HEIGHT / 16 - PADDLE_WIDTH / 2) as i32;
+pub const PADDLE_B_Y: i32 = ((WINDOW_HEIGHT - WINDOW_HEIGHT / 16) - PADDLE_WIDTH / 2) as i32;
+pub const PADDLE_T_WIDTH: u32 = WINDOW_WIDTH / 8;
+pub const PADDLE_B_WIDTH: u32 = WINDOW_WIDTH / 8;
+pub const PADDLE_T_STEP: u32 = PADDLE_T_WIDTH / 8;
+pub const PADDLE_B_STEP: u32 = PADDLE_B_WIDTH / 8;
+pub const AI_DEAD_ZONE: u32 = PADDLE_WIDTH;
 
 pub const MID_LINE_WIDTH: u32 = WINDOW_WIDTH / 160;
 pub const MID_LINE_SEGMENTS: u32 = 32;
@@ -30,14 +76,63 @@ pub const BALL_DIAMETER: u32 = (WINDOW_WIDTH + WINDOW_HEIGHT) / 140;
 pub const BALL_VX: i32 = WINDOW_WIDTH as i32 / 100;
 pub const BALL_VY: i32 = BALL_VX;
 pub const MULTIPLIER: f32 = 2.0;
+pub const RALLY_SPEED_CAP: u32 = 200;
+pub const SPIN_UNIT: i32 = 64;
+pub const SPIN_TRANSFER: i32 = 2;
+pub const SPIN_DECAY: i32 = 16;
 pub const SLOW_START: f32 = 1.5;
+pub const DEBUG_PREDICTION: u32 = FPS as u32;
+pub const DEBUG_VELOCITY_SCALE: i32 = 4;
+pub const SPARKS: usize = 8;
+pub const SPARK_LIFE: u32 = FPS as u32 / 3;
+pub const SPARK_SPEED: f32 = BALL_VX as f32 / 2.0;
+pub const SPARK_SIZE: u32 = BALL_DIAMETER / 3;
+pub const TRAIL_LENGTH: usize = 8;
+pub const SHAKE_TICKS: u32 = FPS as u32 / 4;
+pub const SHAKE_AMPLITUDE: i32 = BALL_DIAMETER as i32;
+pub const FLASH_TICKS: u32 = FPS as u32 / 6;
+pub const FLASH_ALPHA: u8 = 96;
+pub const SCORE_POP_TICKS: u32 = FPS as u32 / 3;
+pub const SCORE_POP_GROW: f32 = 0.3;
+pub const CRT_CURVATURE: f32 = 0.04;
+pub const CRT_VIGNETTE: f32 = 0.35;
+pub const CRT_SCANLINE: f32 = 0.7;
+pub const CRT_BLOOM: u32 = 96;
+pub const CRT_BLOOM_RADIUS: usize = 3;
+pub const CRT_BUDGET_MS: u64 = 8;
+pub const CRT_SLOW_FRAMES: u32 = FPS as u32;
+
+pub const BRICK_WIDTH: u32 = PADDLE_WIDTH * 2;
+pub const BRICK_HEIGHT: u32 = WINDOW_HEIGHT / 16;
+pub const MAX_BRICKS: usize = 1024;
+pub const PICKUP_SIZE: u32 = BALL_DIAMETER * 2;
+pub const GROW: f32 = 1.5;
+pub const SHRINK: f32 = 0.6;
+pub const SPEED_UP: f32 = 1.4;
+pub const SLOW_DOWN: f32 = 0.7;
 
 pub const DISPLAY_COEFFICENT: u32 = (WINDOW_WIDTH + WINDOW_HEIGHT) / 90;
 
 pub const BACKGROUND_COLOR: Color = Color::RGB(0, 0, 0);
 pub const PADDLE_COLOR: Color = Color::RGB(255, 255, 255);
-pub const PADDLE_COLOR_PAUSE: Color = Color::RGB(127, 127, 127);
 pub const MID_LINE_COLOR: Color = Color::RGB(255, 255, 255);
 pub const BALL_COLOR: Color = Color::RGB(255, 255, 0);
-pub const BALL_COLOR_PAUSE: Color = Color::RGB(127, 127, 0);
 pub const DISPLAY_COLOR: Color = Color::RGB(191, 191, 191);
+pub const OBSTACLE_COLOR: Color = Color::RGB(95, 95, 191);
+pub const ZONE_COLOR: Color = Color::RGB(31, 31, 47);
+pub const DEBUG_COLOR: Color = Color::RGB(255, 0, 255);
+pub const DEBUG_PATH_COLOR: Color = Color::RGB(0, 255, 255);
+pub const BRICK_COLORS: [Color; 3] = [
+    Color::RGB(0, 191, 95),
+    Color::RGB(191, 191, 0),
+    Color::RGB(191, 63, 0),
+];
+pub const POWER_UP_COLORS: [Color; 7] = [
+    Color::RGB(0, 255, 0),
+    Color::RGB(255, 0, 0),
+    Color::RGB(255, 127, 0),
+    Color::RGB(0, 127, 255),
+    Color::RGB(255, 255, 255),
+    Color::RGB(255, 0, 255),
+    Color::RGB(0, 255, 255),
+];
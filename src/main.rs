@@ -1,48 +1,139 @@
+use pong::capture::Capture;
+use pong::config::{Config, NetRole};
 use pong::constants::*;
 use pong::game::Game;
-use pong::mid_line::DashedLineVert;
+use pong::net::NetSession;
+use pong::netcode::Netcode;
+use pong::personal_best::PersonalBest;
 use pong::point_display::PointDisplay;
+use pong::sound::Sound;
+use pong::spectator::{SpectatorClient, SpectatorServer};
+use pong::terminal::Terminal;
+use pong::viewer::Viewer;
+use pong::world::World;
 use pong::Result;
-use sdl2::rect::Rect;
+use std::time::Duration;
 
 pub fn main() -> Result<()> {
+    let config = Config::from_args(std::env::args().skip(1))?;
+    if config.terminal {
+        return play_in_terminal(&config);
+    }
+    let session = match &config.net {
+        Some(NetRole::Host(port)) => Some(NetSession::host(*port, config.net_config)?),
+        Some(NetRole::Join(addr)) => Some(NetSession::join(addr, config.net_config)?),
+        None => None,
+    };
+    let net = session.map(|session| Netcode::new(session, config.rollback));
+    let spectate = match &config.spectate {
+        Some(addr) => Some(SpectatorClient::connect(
+            addr,
+            Duration::from_millis(SPECTATOR_DELAY),
+        )?),
+        None => None,
+    };
+
+    let mut capture = Capture::new(config.frames, config.screenshot.clone());
+    if let Some(path) = &config.record {
+        capture.record(path.clone(), config.record_every)?;
+    }
+
+    if config.headless {
+        sdl2::hint::set("SDL_VIDEODRIVER", "dummy");
+    }
     let sdl_context = sdl2::init()?;
     let video_subsystem = sdl_context.video()?;
 
-    let window = video_subsystem
-        .window("Pong", WINDOW_WIDTH, WINDOW_HEIGHT)
-        .position_centered()
-        .opengl()
-        .build()?;
+    let mut window = video_subsystem.window("Pong", WINDOW_WIDTH, WINDOW_HEIGHT);
+    window.position_centered();
+    if config.headless {
+        window.hidden();
+    } else {
+        window.opengl().resizable();
+    }
+    if config.fullscreen {
+        window.fullscreen_desktop();
+    }
+    let window = window.build()?;
 
-    let mut canvas = window.into_canvas().accelerated().build()?;
+    let mut canvas = if config.headless {
+        window.into_canvas().software().build()?
+    } else {
+        window.into_canvas().accelerated().build()?
+    };
+    // everything is drawn in logical units, scaled to the window without smoothing
+    sdl2::hint::set("SDL_RENDER_SCALE_QUALITY", "0");
+    canvas.set_logical_size(WINDOW_WIDTH, WINDOW_HEIGHT)?;
 
-    canvas.set_draw_color(BACKGROUND_COLOR);
+    let theme = config.theme;
+    canvas.set_draw_color(theme.background);
     canvas.clear();
     canvas.present();
     let events = sdl_context.event_pump()?;
 
     let texture_creator = canvas.texture_creator();
-    let pos = Rect::new(
-        (WINDOW_WIDTH - MID_LINE_WIDTH) as i32 / 2,
-        0,
-        MID_LINE_WIDTH,
-        WINDOW_HEIGHT,
-    );
-    let mid_line = DashedLineVert::new(
+    let point_display = PointDisplay::new(
         &texture_creator,
         &mut canvas,
-        pos,
-        MID_LINE_SEGMENTS,
-        1,
-        1,
-        MID_LINE_COLOR,
-        BACKGROUND_COLOR,
+        config.mode.counters(),
+        theme.display,
+        theme.background,
     )?;
 
-    let point_display = PointDisplay::new(&texture_creator, &mut canvas)?;
+    if let Some(client) = spectate {
+        let mut viewer = Viewer::new(canvas, point_display, events, client, FPS);
+        viewer.set_capture(capture);
+        viewer.set_theme(theme);
+        viewer.set_crt(config.crt);
+        return viewer.spawn();
+    }
 
-    let mut game = Game::new(canvas, point_display, events, mid_line, FPS);
+    let mut game = Game::new(canvas, point_display, events, FPS, net, config.mode);
+    game.set_cpu(&config.cpu)?;
+    game.set_multi_ball(config.multi_ball);
+    game.set_rally_speed(config.rally_speed.clone());
+    game.set_power_ups(&config.power_ups);
+    game.set_arena(config.arena.clone());
+    game.set_bricks(config.bricks.clone());
+    game.set_rules(config.rules);
+    game.set_serve_timeout(config.serve_timeout);
+    game.set_spin(config.spin);
+    game.set_debug(config.debug);
+    game.set_capture(capture);
+    game.set_theme(theme);
+    game.set_reduced_motion(config.reduced_motion);
+    game.set_crt(config.crt);
+    // a hidden window never has the focus
+    game.set_auto_pause(config.auto_pause && !config.headless);
+    game.set_resume_countdown(config.resume_countdown);
+    game.set_countdown(config.countdown);
+    if !config.headless && !config.mute {
+        game.set_sound(Sound::new(&sdl_context));
+    }
+    if config.mode.is_solo() {
+        game.set_personal_best(PersonalBest::load(PersonalBest::default_path()));
+    }
+    if let Some(port) = config.broadcast {
+        game.set_spectators(SpectatorServer::bind(port)?);
+    }
     game.spawn()?;
     Ok(())
 }
+
+/// the same match as in the window, without the options that need one
+fn play_in_terminal(config: &Config) -> Result<()> {
+    let mut world = World::new(config.mode, rand::random());
+    world.set_multi_ball(config.multi_ball);
+    world.set_rally_speed(config.rally_speed.clone());
+    world.set_power_ups(&config.power_ups);
+    world.set_arena(config.arena.clone());
+    world.set_bricks(config.bricks.clone());
+    world.set_rules(config.rules);
+    world.set_serve_timeout(config.serve_timeout);
+    world.set_spin(config.spin);
+    world.set_countdown(config.countdown);
+    let mut terminal = Terminal::new(world, FPS);
+    terminal.set_cpu(&config.cpu)?;
+    terminal.set_theme(config.theme);
+    terminal.spawn()
+}
@@ -5,7 +5,30 @@ pub mod team;
 pub mod history;
 pub mod virtual_ball;
 pub mod point_display;
-pub mod mid_line;
 pub mod game_status;
 pub mod game;
 pub mod error;
+pub mod ai;
+pub mod packet;
+pub mod world;
+pub mod net;
+pub mod lockstep;
+pub mod config;
+pub mod rollback;
+pub mod netcode;
+pub mod spectator;
+pub mod viewer;
+pub mod mode;
+pub mod powerup;
+pub mod arena;
+pub mod rules;
+pub mod personal_best;
+pub mod bricks;
+pub mod frame_stats;
+pub mod capture;
+pub mod terminal;
+pub mod theme;
+pub mod juice;
+pub mod crt;
+pub mod decorations;
+pub mod sound;
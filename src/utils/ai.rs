@@ -0,0 +1,133 @@
+use crate::ball::Ball;
+use crate::constants::*;
+use crate::paddle::{Paddle, PaddleMove};
+use crate::team::TeamName;
+use crate::world::World;
+
+#[derive(Debug)]
+pub struct Ai {
+    side: TeamName,
+    dead_zone: i32,
+}
+
+impl Ai {
+    pub fn new(side: TeamName, dead_zone: u32) -> Self {
+        Self {
+            side,
+            dead_zone: dead_zone as i32,
+        }
+    }
+
+    /// one for each paddle of `world`, in the same order
+    pub fn for_paddles(world: &World) -> Vec<Self> {
+        world
+            .paddles()
+            .iter()
+            .map(|paddle| Self::new(paddle.team(), AI_DEAD_ZONE))
+            .collect()
+    }
+
+    /// the move of the paddle `i` of `world`, anywhere along its wall
+    pub fn paddle_move(&self, world: &World, i: usize) -> PaddleMove {
+        let paddle = &world.paddles()[i];
+        let max = if paddle.is_horizontal() {
+            WINDOW_WIDTH
+        } else {
+            WINDOW_HEIGHT
+        };
+        self.next_move(paddle, world.balls(), 0, max as i32)
+    }
+
+    /// follows the closest ball coming towards the paddle, otherwise goes back to the centre;
+    /// `min` and `max` are the limits along the direction the paddle moves
+    pub fn next_move(&self, paddle: &Paddle, balls: &[Ball], min: i32, max: i32) -> PaddleMove {
+        let incoming = balls
+            .iter()
+            .filter(|ball| match self.side {
+                TeamName::Left => ball.vx() < 0,
+                TeamName::Right => ball.vx() > 0,
+                TeamName::Top => ball.vy() < 0,
+                TeamName::Bottom => ball.vy() > 0,
+            })
+            .min_by_key(|ball| {
+                if self.side.is_horizontal() {
+                    (ball.y() - paddle.y()).abs()
+                } else {
+                    (ball.x() - paddle.x()).abs()
+                }
+            });
+        let center = if self.side.is_horizontal() {
+            paddle.x() + paddle.width() / 2
+        } else {
+            paddle.y() + paddle.height() / 2
+        };
+        let target = match incoming {
+            Some(ball) if self.side.is_horizontal() => ball.x() + ball.width() / 2,
+            Some(ball) => ball.y() + ball.height() / 2,
+            None => (min + max) / 2,
+        };
+        if target < center - self.dead_zone {
+            PaddleMove::Up
+        } else if target > center + self.dead_zone {
+            PaddleMove::Down
+        } else {
+            PaddleMove::Stay
+        }
+    }
+}
+
+#[test]
+fn test() {
+    let paddle = Paddle::new(TeamName::Left, 0, 300, 10, 100, 10);
+    let ai = Ai::new(TeamName::Left, 10);
+    let mut ball = Ball::new_rng(600, 100, 10, 10, 0, 2.0, 1.0);
+    ball.after_goal_rng(600, 100, TeamName::Left);
+    assert_eq!(
+        ai.next_move(&paddle, &[ball.clone()], 0, 720),
+        PaddleMove::Up
+    );
+    ball.after_goal_rng(600, 600, TeamName::Left);
+    assert_eq!(
+        ai.next_move(&paddle, &[ball.clone()], 0, 720),
+        PaddleMove::Down
+    );
+    let ai = Ai::new(TeamName::Right, 10);
+    assert_eq!(
+        ai.next_move(&paddle, &[ball.clone()], 0, 720),
+        PaddleMove::Stay
+    );
+
+    let paddle = Paddle::new(TeamName::Top, 500, 40, 160, 10, 20);
+    let ai = Ai::new(TeamName::Top, 10);
+    let mut ball = Ball::new_rng(100, 300, 10, 10, 10, 2.0, 1.0);
+    ball.after_goal_rng(100, 300, TeamName::Top);
+    assert_eq!(
+        ai.next_move(&paddle, &[ball.clone()], 0, 1280),
+        PaddleMove::Up
+    );
+}
+
+#[test]
+fn test_doubles() {
+    use crate::config::Config;
+
+    let args = "--mode doubles --cpu 1,2,3,4";
+    let config = Config::from_args(args.split(' ').map(String::from)).unwrap();
+    let mut world = World::new(config.mode, 5);
+    let ais = Ai::for_paddles(&world);
+    let start: Vec<_> = world.paddles().iter().map(|paddle| paddle.y()).collect();
+    let mut moved = vec![false; start.len()];
+    for _ in 0..500 {
+        let moves: Vec<_> = config
+            .cpu
+            .iter()
+            .map(|paddle| ais[paddle - 1].paddle_move(&world, paddle - 1))
+            .collect();
+        world.step(&moves).unwrap();
+        for (moved, (paddle, start)) in moved.iter_mut().zip(world.paddles().iter().zip(&start)) {
+            *moved |= paddle.y() != *start;
+        }
+    }
+    // the forward paddles too
+    assert_eq!(moved, [true; 4]);
+}
@@ -0,0 +1,291 @@
+use crate::constants::*;
+use crate::decorations::Decoration;
+use crate::error::{InvalidArgument, InvalidLayout};
+use crate::segment::Segmet2D;
+use crate::team::TeamName;
+use crate::Result;
+use sdl2::rect::Rect;
+
+const BUILTIN: [(&str, &str); 5] = [
+    ("classic", ""),
+    ("pillars", "rect 470 150 60 110\nrect 470 740 60 110\n"),
+    (
+        "gates",
+        "goal left 300 700\ngoal right 300 700\ngoal-zone 40\nboundary solid\n",
+    ),
+    (
+        "bumpers",
+        "wall 440 0 500 130\nwall 500 130 560 0\nwall 440 1000 500 870\nwall 500 870 560 1000\n",
+    ),
+    (
+        "movers",
+        "moving 330 0 15 250 330 750 240\nmoving 655 750 15 250 655 0 240\n",
+    ),
+];
+
+/// what the ball bounces on besides the paddles
+#[derive(Debug, Clone, PartialEq)]
+pub struct Court {
+    pub x_min: i32,
+    pub x_max: i32,
+    pub y_min: i32,
+    pub y_max: i32,
+    /// the part of a side that is a goal, the rest of the side bounces the ball
+    pub goals: Vec<(TeamName, i32, i32)>,
+    pub rects: Vec<Rect>,
+    pub walls: Vec<Segmet2D>,
+    /// breakable, with the index that `Contacts::brick` reports
+    pub bricks: Vec<(usize, Rect)>,
+}
+
+impl Court {
+    /// every side in `goals` is a goal for its whole length
+    pub fn new(x_min: i32, x_max: i32, y_min: i32, y_max: i32, goals: &[TeamName]) -> Self {
+        let goals = goals
+            .iter()
+            .map(|team| match team.is_horizontal() {
+                true => (*team, x_min, x_max),
+                false => (*team, y_min, y_max),
+            })
+            .collect();
+        Self {
+            x_min,
+            x_max,
+            y_min,
+            y_max,
+            goals,
+            rects: vec![],
+            walls: vec![],
+            bricks: vec![],
+        }
+    }
+
+    pub fn goal(&self, team: TeamName) -> Option<(i32, i32)> {
+        self.goals
+            .iter()
+            .find(|(goal, ..)| *goal == team)
+            .map(|(_, from, to)| (*from, *to))
+    }
+}
+
+/// a rectangle going back and forth between `rect` and `to` in `period` ticks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Obstacle {
+    rect: Rect,
+    to: (i32, i32),
+    period: u32,
+}
+
+impl Obstacle {
+    pub fn rect_at(&self, tick: u32) -> Rect {
+        if self.period == 0 {
+            return self.rect;
+        }
+        let half = (self.period / 2).max(1) as i32;
+        let phase = (tick % self.period) as i32;
+        let travelled = if phase < half {
+            phase
+        } else {
+            2 * half - phase
+        };
+        let x = self.rect.x() + (self.to.0 - self.rect.x()) * travelled / half;
+        let y = self.rect.y() + (self.to.1 - self.rect.y()) * travelled / half;
+        Rect::new(x, y, self.rect.width(), self.rect.height())
+    }
+}
+
+/// obstacles and goal mouths; layout files use a court of 1000 x 1000 units:
+///
+/// ```text
+/// # a comment
+/// rect X Y W H
+/// wall X1 Y1 X2 Y2
+/// moving X Y W H TO_X TO_Y PERIOD_TICKS
+/// goal left|right|top|bottom FROM TO
+/// ```
+///
+/// and the decorations of the court, as in a theme, over the ones of the theme
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Arena {
+    obstacles: Vec<Obstacle>,
+    walls: Vec<Segmet2D>,
+    mouths: Vec<(TeamName, i32, i32)>,
+    decorations: Vec<Decoration>,
+}
+
+impl Arena {
+    pub fn builtin(name: &str) -> Option<Self> {
+        let (_, layout) = BUILTIN.iter().find(|(builtin, _)| *builtin == name)?;
+        Self::parse(layout).ok()
+    }
+
+    pub fn builtin_names() -> impl Iterator<Item = &'static str> {
+        BUILTIN.iter().map(|(name, _)| *name)
+    }
+
+    /// a built-in name or the path of a layout file
+    pub fn load(name: &str) -> Result<Self> {
+        match Self::builtin(name) {
+            Some(arena) => Ok(arena),
+            None => Self::parse(&std::fs::read_to_string(name)?),
+        }
+    }
+
+    pub fn parse(layout: &str) -> Result<Self> {
+        let mut arena = Self::default();
+        for (i, line) in layout.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default();
+            let mut words = line.split_whitespace();
+            let Some(command) = words.next() else {
+                continue;
+            };
+            let error = || InvalidLayout(format!("line {}: {}", i + 1, line.trim()));
+            if Decoration::KEYWORDS.contains(&command) {
+                let words: Vec<_> = words.collect();
+                let decoration = Decoration::parse(command, &words).ok_or_else(error)?;
+                arena.decorations.push(decoration);
+                continue;
+            }
+            let side = match command {
+                "goal" => Some(match words.next() {
+                    Some("left") => TeamName::Left,
+                    Some("right") => TeamName::Right,
+                    Some("top") => TeamName::Top,
+                    Some("bottom") => TeamName::Bottom,
+                    _ => return Err(Box::new(error())),
+                }),
+                _ => None,
+            };
+            let numbers = words
+                .map(|word| word.parse::<i32>())
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map_err(|_| error())?;
+            // every shape stays in the court, 0..1000 both ways, which also
+            // keeps the scaling below from overflowing
+            let inside = |from: i32, size: i32| {
+                from >= 0 && size >= 0 && from.checked_add(size).is_some_and(|to| to <= 1000)
+            };
+            let fits = match (command, numbers.as_slice()) {
+                ("rect", [rx, ry, w, h]) => inside(*rx, *w) && inside(*ry, *h),
+                ("moving", [rx, ry, w, h, to_x, to_y, _]) => {
+                    inside(*rx, *w) && inside(*ry, *h) && inside(*to_x, *w) && inside(*to_y, *h)
+                }
+                (_, numbers) => numbers.iter().all(|units| inside(*units, 0)),
+            };
+            if !fits {
+                let line = format!("line {}: {}", i + 1, line.trim());
+                return Err(Box::new(InvalidArgument(line)));
+            }
+            let x = |units: i32| units * WINDOW_WIDTH as i32 / 1000;
+            let y = |units: i32| units * WINDOW_HEIGHT as i32 / 1000;
+            match (command, numbers.as_slice(), side) {
+                ("rect", [rx, ry, w, h], _) => arena.obstacles.push(Obstacle {
+                    rect: Rect::new(x(*rx), y(*ry), x(*w).max(1) as u32, y(*h).max(1) as u32),
+                    to: (x(*rx), y(*ry)),
+                    period: 0,
+                }),
+                ("moving", [rx, ry, w, h, to_x, to_y, period], _) => {
+                    arena.obstacles.push(Obstacle {
+                        rect: Rect::new(x(*rx), y(*ry), x(*w).max(1) as u32, y(*h).max(1) as u32),
+                        to: (x(*to_x), y(*to_y)),
+                        period: (*period).max(0) as u32,
+                    })
+                }
+                ("wall", [x1, y1, x2, y2], _) => {
+                    arena
+                        .walls
+                        .push(Segmet2D::new(x(*x1), y(*y1), x(*x2), y(*y2)))
+                }
+                ("goal", [from, to], Some(team)) => {
+                    let (from, to) = match team.is_horizontal() {
+                        true => (x(*from), x(*to)),
+                        false => (y(*from), y(*to)),
+                    };
+                    arena.mouths.push((team, from.min(to), from.max(to)));
+                }
+                _ => return Err(Box::new(error())),
+            }
+        }
+        Ok(arena)
+    }
+
+    pub fn obstacles(&self, tick: u32) -> Vec<Rect> {
+        self.obstacles
+            .iter()
+            .map(|obstacle| obstacle.rect_at(tick))
+            .collect()
+    }
+
+    pub fn walls(&self) -> &[Segmet2D] {
+        &self.walls
+    }
+
+    pub fn decorations(&self) -> &[Decoration] {
+        &self.decorations
+    }
+
+    /// the whole window, with the goal mouths of `goals`
+    pub fn court(&self, tick: u32, goals: &[TeamName]) -> Court {
+        let mut court = Court::new(0, WINDOW_WIDTH as i32, 0, WINDOW_HEIGHT as i32, goals);
+        for (team, from, to) in &self.mouths {
+            if let Some(goal) = court.goals.iter_mut().find(|(goal, ..)| goal == team) {
+                *goal = (*team, *from, *to);
+            }
+        }
+        court.rects = self.obstacles(tick);
+        court.walls = self.walls.clone();
+        court
+    }
+
+    /// what is drawn on the sides of `teams` outside of their goal mouth
+    pub fn posts(&self, teams: &[TeamName]) -> Vec<Rect> {
+        let (width, height) = (WINDOW_WIDTH as i32, WINDOW_HEIGHT as i32);
+        let thickness = (PADDLE_WIDTH / 2) as i32;
+        let mut posts = vec![];
+        for (team, from, to) in self.mouths.iter().filter(|(team, ..)| teams.contains(team)) {
+            let length = if team.is_horizontal() { width } else { height };
+            for (start, end) in [(0, *from), (*to, length)] {
+                if end <= start {
+                    continue;
+                }
+                let size = (end - start) as u32;
+                posts.push(match team {
+                    TeamName::Left => Rect::new(0, start, thickness as u32, size),
+                    TeamName::Right => Rect::new(width - thickness, start, thickness as u32, size),
+                    TeamName::Top => Rect::new(start, 0, size, thickness as u32),
+                    TeamName::Bottom => {
+                        Rect::new(start, height - thickness, size, thickness as u32)
+                    }
+                });
+            }
+        }
+        posts
+    }
+}
+
+#[test]
+fn test() {
+    for name in Arena::builtin_names() {
+        assert!(Arena::builtin(name).is_some());
+    }
+    let arena = Arena::parse("goal left 250 750 # narrow\nmoving 0 0 10 10 500 0 100").unwrap();
+    let court = arena.court(0, &[TeamName::Left, TeamName::Right]);
+    assert_eq!(court.goal(TeamName::Left), Some((180, 540)));
+    assert_eq!(court.goal(TeamName::Right), Some((0, 720)));
+    assert_eq!(arena.obstacles(50)[0].x(), 640);
+    assert_eq!(arena.obstacles(100)[0].x(), 0);
+    assert_eq!(arena.posts(&[TeamName::Left]).len(), 2);
+    assert!(Arena::parse("rect 1 2 3").is_err());
+    assert!(Arena::parse("rect 900 0 200 10").is_err());
+    assert!(Arena::parse("rect 2147483647 0 1 1").is_err());
+    assert!(Arena::parse("moving 0 0 10 10 995 0 100").is_err());
+    assert!(Arena::parse("wall -1 0 10 10").is_err());
+    assert!(Arena::parse("goal left 0 1001").is_err());
+    assert!(Arena::parse("goal middle 1 2").is_err());
+    let arena = Arena::parse("circle 200\ngoal-zone 50").unwrap();
+    assert_eq!(
+        arena.decorations(),
+        [Decoration::Circle(200), Decoration::GoalZone(50)]
+    );
+    assert!(Arena::parse("circle big").is_err());
+}
@@ -1,13 +1,29 @@
+use crate::arena::Court;
+use crate::constants::*;
 use crate::history::History;
 use crate::paddle::Paddle;
 use crate::segment::Segmet2D;
 use crate::team::TeamName;
 use crate::virtual_ball::VirtualBall;
 use crate::Result;
-use rand::Rng;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 use sdl2::rect::Rect;
 
-#[derive(Debug)]
+/// what the ball touched during a tick
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Contacts {
+    /// the team whose goal has been hit
+    pub goal: Option<TeamName>,
+    /// index of the last paddle hit, in the slice given to `change_position`
+    pub paddle: Option<usize>,
+    /// index of the last brick hit, as given by the court
+    pub brick: Option<usize>,
+    /// the ball bounced on a side of the court, an obstacle or a wall
+    pub wall: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Ball {
     rect: Rect,
     vx: i32,
@@ -17,6 +33,11 @@ pub struct Ball {
     vy_default: i32,
     multplier_max: f32,
     slow_start: f32,
+    /// added to the velocity every tick, in `1 / SPIN_UNIT` pixels per tick
+    spin: (i32, i32),
+    /// what is left of the spin once the whole pixels are added to the velocity
+    spin_rest: (i32, i32),
+    rng: ChaCha8Rng,
 }
 
 impl Ball {
@@ -29,33 +50,136 @@ impl Ball {
         multplier_max: f32,
         slow_start: f32,
     ) -> Self {
-        let vx_rng = if rand::thread_rng().gen_bool(0.5) {
-            (vx as f32 / slow_start) as i32
-        } else {
-            (-vx as f32 / slow_start) as i32
-        };
-        let vy_rng = ((rand::thread_rng().gen_range(-1000..=1000) * vy) as f32 / 1000.0) as i32;
         let rect = Rect::new(x, y, diameter, diameter);
-        Self {
+        let mut ball = Self {
             rect,
-            vx: vx_rng,
-            vy: vy_rng,
+            vx: 0,
+            vy: 0,
             rect_default: rect,
             vx_default: vx.abs(),
             vy_default: vy.abs(),
             multplier_max,
             slow_start,
-        }
+            spin: (0, 0),
+            spin_rest: (0, 0),
+            rng: ChaCha8Rng::from_entropy(),
+        };
+        ball.reset_rng();
+        ball
+    }
+
+    /// same ball, but every random choice is taken from `seed`
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = ChaCha8Rng::seed_from_u64(seed);
+        self.reset_rng();
+        self
+    }
+
+    pub fn reset_rng(&mut self) {
+        self.rect = self.rect_default;
+        self.set_spin(0, 0);
+        let vx = (self.vx_default() as f32 / self.slow_start()) as i32;
+        self.vx = if self.rng.gen_bool(0.5) { vx } else { -vx };
+        self.vy = self.vy_rng();
     }
 
     pub fn after_goal_rng(&mut self, x: i32, y: i32, direction: TeamName) {
         self.rect = Rect::new(x, y, self.width() as u32, self.height() as u32);
-        self.vx = match direction {
-            TeamName::Right => (self.vx_default() as f32 / self.slow_start()) as i32,
-            TeamName::Left => -(self.vx_default() as f32 / self.slow_start()) as i32,
+        self.set_spin(0, 0);
+        let vx = (self.vx_default() as f32 / self.slow_start()) as i32;
+        let vy = (self.vy_default() as f32 / self.slow_start()) as i32;
+        match direction {
+            TeamName::Right => (self.vx, self.vy) = (vx, self.vy_rng()),
+            TeamName::Left => (self.vx, self.vy) = (-vx, self.vy_rng()),
+            TeamName::Bottom => (self.vx, self.vy) = (self.vx_rng(), vy),
+            TeamName::Top => (self.vx, self.vy) = (self.vx_rng(), -vy),
+        }
+    }
+
+    /// launched away from the wall of `server`, `spin` from -1 to 1 gives the angle
+    pub fn serve(&mut self, server: TeamName, spin: i32) {
+        self.set_spin(0, 0);
+        let vx = (self.vx_default() as f32 / self.slow_start()) as i32;
+        let vy = (self.vy_default() as f32 / self.slow_start()) as i32;
+        (self.vx, self.vy) = match server {
+            TeamName::Left => (vx, spin * vy),
+            TeamName::Right => (-vx, spin * vy),
+            TeamName::Top => (spin * vx, vy),
+            TeamName::Bottom => (spin * vx, -vy),
         };
-        self.vy = ((rand::thread_rng().gen_range(-1000..=1000) * self.vy_default()) as f32 / 1000.0)
-            as i32;
+    }
+
+    fn vx_rng(&mut self) -> i32 {
+        ((self.rng.gen_range(-1000..=1000) * self.vx_default()) as f32 / 1000.0) as i32
+    }
+
+    fn vy_rng(&mut self) -> i32 {
+        ((self.rng.gen_range(-1000..=1000) * self.vy_default()) as f32 / 1000.0) as i32
+    }
+
+    /// the current velocity is scaled too
+    pub fn set_speed(&mut self, vx_default: i32, vy_default: i32) {
+        if self.vx_default != 0 {
+            self.vx = self.vx * vx_default.abs() / self.vx_default;
+        }
+        if self.vy_default != 0 {
+            self.vy = self.vy * vy_default.abs() / self.vy_default;
+        }
+        self.vx_default = vx_default.abs();
+        self.vy_default = vy_default.abs();
+    }
+
+    pub fn set_motion(&mut self, x: i32, y: i32, vx: i32, vy: i32) {
+        self.rect.x = x;
+        self.rect.y = y;
+        self.vx = vx;
+        self.vy = vy;
+    }
+
+    pub fn spin(&self) -> (i32, i32) {
+        self.spin
+    }
+
+    pub fn spin_rest(&self) -> (i32, i32) {
+        self.spin_rest
+    }
+
+    pub fn set_spin(&mut self, x: i32, y: i32) {
+        self.spin = (x, y);
+        self.spin_rest = (0, 0);
+    }
+
+    pub fn set_spin_rest(&mut self, x: i32, y: i32) {
+        self.spin_rest = (x, y);
+    }
+
+    /// the spin curves the path of the ball and fades away
+    fn apply_spin(&mut self) {
+        let curve = |v: i32, spin: i32, rest: i32, max: i32| {
+            let total = spin + rest;
+            let v = if spin == 0 {
+                v
+            } else {
+                (v + total / SPIN_UNIT).clamp(-max, max)
+            };
+            let spin = spin - spin / SPIN_DECAY - spin.signum();
+            (v, spin, total % SPIN_UNIT)
+        };
+        let max_x = (self.vx_default as f32 * self.multplier_max) as i32;
+        let max_y = (self.vy_default as f32 * self.multplier_max) as i32;
+        let (vx, spin_x, rest_x) = curve(self.vx, self.spin.0, self.spin_rest.0, max_x);
+        let (vy, spin_y, rest_y) = curve(self.vy, self.spin.1, self.spin_rest.1, max_y);
+        (self.vx, self.vy) = (vx, vy);
+        self.spin = (spin_x, spin_y);
+        self.spin_rest = (rest_x, rest_y);
+    }
+
+    pub fn rng_position(&self) -> u128 {
+        self.rng.get_word_pos()
+    }
+
+    pub fn set_rng_position(&mut self, position: u128) {
+        self.rng.set_word_pos(position);
     }
 
     pub fn x(&self) -> i32 {
@@ -102,10 +226,32 @@ impl Ball {
         self.rect.height() as i32
     }
 
+    pub fn center(&self) -> (i32, i32) {
+        (self.x() + self.width() / 2, self.y() + self.height() / 2)
+    }
+
     pub fn rect_default(&self) -> Rect {
         self.rect_default
     }
 
+    /// balls of the same mass: the velocities along the direction of the impact are exchanged
+    pub fn collide(&mut self, other: &mut Ball) {
+        let overlap_x =
+            (self.x() + self.width()).min(other.x() + other.width()) - self.x().max(other.x());
+        let overlap_y =
+            (self.y() + self.height()).min(other.y() + other.height()) - self.y().max(other.y());
+        if overlap_x <= 0 || overlap_y <= 0 {
+            return;
+        }
+        if overlap_x < overlap_y {
+            if (other.x() - self.x()) * (self.vx - other.vx) > 0 {
+                std::mem::swap(&mut self.vx, &mut other.vx);
+            }
+        } else if (other.y() - self.y()) * (self.vy - other.vy) > 0 {
+            std::mem::swap(&mut self.vy, &mut other.vy);
+        }
+    }
+
     fn move_x_unchecked(&mut self, next_x: i32) {
         self.rect.x = next_x;
     }
@@ -138,19 +284,38 @@ impl Ball {
         self.move_y_unchecked(next_y);
     }
 
-    pub fn change_position(
-        &mut self,
-        paddles: Option<(&Paddle, &Paddle)>,
-        x_min: i32,
-        x_max: i32,
-        y_min: i32,
-        y_max: i32,
-    ) -> Result<Option<TeamName>> {
-        let walls = self.build_walls(paddles, x_min, x_max, y_min, y_max);
+    pub fn move_left(&mut self, steps: u32, left: i32) {
+        let virtual_x = self.x() - steps as i32;
+        let next_x = if virtual_x > left { virtual_x } else { left };
+        self.move_x_unchecked(next_x);
+    }
+
+    pub fn move_right(&mut self, steps: u32, right: i32) {
+        let virtual_x = self.x() + steps as i32;
+        let next_x = if virtual_x + self.width() < right {
+            virtual_x
+        } else {
+            right - self.width()
+        };
+        self.move_x_unchecked(next_x);
+    }
+
+    /// the sides of `court` bounce the ball, except for the goal mouths
+    pub fn change_position(&mut self, paddles: &[&Paddle], court: &Court) -> Result<Contacts> {
+        self.apply_spin();
+        if self.bounce_diagonal(&court.walls) {
+            return Ok(Contacts {
+                wall: true,
+                ..Default::default()
+            });
+        }
+        let (walls, bricks) = self.build_walls(paddles, court);
+        let first_brick = 1 + paddles.len() + court.rects.len();
         let mut step_x = History::new(3, i32::MAX);
         step_x.push(self.vx());
         let mut step_y = History::new(3, i32::MAX);
         step_y.push(self.vy());
+        let mut contacts = Contacts::default();
 
         loop {
             let vb_1 = if step_x.first() >= 0 {
@@ -179,42 +344,129 @@ impl Ball {
             self.move_y_unchecked(vb.y());
 
             if vb.indexes().is_none() {
-                return Ok(None);
+                return Ok(contacts);
             }
             let (id, index) = vb.indexes().expect("It can't be None");
+            // index 0 is the border of the court, then one segment for each paddle,
+            // then the obstacles
+            let paddle = index.checked_sub(1).and_then(|i| paddles.get(i));
+            if paddle.is_some() {
+                contacts.paddle = Some(index - 1);
+            }
+            let brick = index.checked_sub(first_brick).and_then(|i| bricks.get(i));
+            if let Some(brick) = brick {
+                contacts.brick = Some(court.bricks[*brick].0);
+            }
+            contacts.wall |= index > 0 && paddle.is_none() && brick.is_none();
 
             if id < 2 {
-                let next_vx = self.vx_default().abs() * vb.step_x().signum();
-                self.modify_vx_unchecked(next_vx);
-                if index != 0 {
-                    let min = walls[id][index].y_min() - self.height();
-                    let max = walls[id][index].y_max();
-                    let mid = (min + max) / 2;
-                    let mul = self.multplier_max() * (vb.y() - mid) as f32 / (mid - min) as f32;
-                    let next_vy = (self.vy_default() as f32 * mul) as i32;
-                    self.modify_vy_unchecked(next_vy);
+                match paddle {
+                    Some(paddle) if !paddle.is_horizontal() => {
+                        let next_vx = self.vx_default().abs() * vb.step_x().signum();
+                        self.modify_vx_unchecked(next_vx);
+                        let min = walls[id][index].y_min() - self.height();
+                        let max = walls[id][index].y_max();
+                        let mid = (min + max) / 2;
+                        let mul = self.multplier_max() * (vb.y() - mid) as f32 / (mid - min) as f32;
+                        let next_vy = (self.vy_default() as f32 * mul) as i32;
+                        self.modify_vy_unchecked(next_vy);
+                    }
+                    _ => {
+                        let next_vx = self.vx().abs() * vb.step_x().signum();
+                        self.modify_vx_unchecked(next_vx);
+                    }
                 }
             } else {
-                let next_vy = self.vy().abs() * vb.step_y().signum();
-                self.modify_vy_unchecked(next_vy);
+                match paddle {
+                    Some(paddle) if paddle.is_horizontal() => {
+                        let next_vy = self.vy_default().abs() * vb.step_y().signum();
+                        self.modify_vy_unchecked(next_vy);
+                        let min = walls[id][index].x_min() - self.width();
+                        let max = walls[id][index].x_max();
+                        let mid = (min + max) / 2;
+                        let mul = self.multplier_max() * (vb.x() - mid) as f32 / (mid - min) as f32;
+                        let next_vx = (self.vx_default() as f32 * mul) as i32;
+                        self.modify_vx_unchecked(next_vx);
+                    }
+                    _ => {
+                        let next_vy = self.vy().abs() * vb.step_y().signum();
+                        self.modify_vy_unchecked(next_vy);
+                    }
+                }
             }
 
             step_x.push(vb.step_x());
             step_y.push(vb.step_y());
 
-            if id == 0 && index == 0 {
-                return Ok(Some(TeamName::Left));
-            }
-            if id == 1 && index == 0 {
-                return Ok(Some(TeamName::Right));
+            if index == 0 {
+                let side = match id {
+                    0 => TeamName::Right,
+                    1 => TeamName::Left,
+                    2 => TeamName::Bottom,
+                    _ => TeamName::Top,
+                };
+                // the whole ball has to get through the mouth
+                let (start, end) = if side.is_horizontal() {
+                    (
+                        self.x().max(court.x_min),
+                        (self.x() + self.width()).min(court.x_max),
+                    )
+                } else {
+                    (
+                        self.y().max(court.y_min),
+                        (self.y() + self.height()).min(court.y_max),
+                    )
+                };
+                if court
+                    .goal(side)
+                    .is_some_and(|(from, to)| from <= start && end <= to)
+                {
+                    contacts.goal = Some(side);
+                    return Ok(contacts);
+                }
+                contacts.wall = true;
             }
 
             let stuck =
                 step_x.first() == step_x.get_value(2)? && step_y.first() == step_y.get_value(2)?;
             if stuck {
-                return Ok(None);
+                return Ok(contacts);
+            }
+        }
+    }
+
+    /// the walls checked by `change_position` for the current direction of the ball
+    pub fn walls(&self, paddles: &[&Paddle], court: &Court) -> Vec<Segmet2D> {
+        let (mut walls, _) = self.build_walls(paddles, court);
+        let horizontal = walls.swap_remove(if self.vy >= 0 { 2 } else { 3 });
+        let vertical = walls.swap_remove(if self.vx >= 0 { 0 } else { 1 });
+        let diagonal = court
+            .walls
+            .iter()
+            .filter(|wall| !wall.is_vertical() && !wall.is_horizontal())
+            .cloned();
+        vertical
+            .into_iter()
+            .chain(horizontal)
+            .chain(diagonal)
+            .collect()
+    }
+
+    /// the centre of the ball in the next `ticks` ticks, if the paddles stay still;
+    /// it stops at the first goal
+    pub fn predict(&self, paddles: &[&Paddle], court: &Court, ticks: u32) -> Vec<(i32, i32)> {
+        let mut ball = self.clone();
+        let mut path = vec![ball.center()];
+        for _ in 0..ticks {
+            let Ok(contacts) = ball.change_position(paddles, court) else {
+                break;
+            };
+            path.push(ball.center());
+            if contacts.goal.is_some() {
+                break;
             }
         }
+        path
     }
 
     fn virtual_wall_right(
@@ -225,7 +477,7 @@ impl Ball {
         step_y: i32,
     ) -> VirtualBall {
         let mut vb = VirtualBall::new(self.x() + step_x, self.y() + step_y, 0, 0, None);
-        for i in 0..walls_right.len() {
+        for (i, wall) in walls_right.iter().enumerate() {
             let movement_top_right = Segmet2D::new(
                 self.x() + self.width(),
                 self.y(),
@@ -238,7 +490,7 @@ impl Ball {
                 vb.x() + self.width(),
                 vb.y() + self.height(),
             );
-            if let Some((x, y)) = movement_top_right.intersect(&walls_right[i]) {
+            if let Some((x, y)) = movement_top_right.intersect(wall) {
                 vb = VirtualBall::new(
                     x - self.width(),
                     y,
@@ -250,7 +502,7 @@ impl Ball {
                         None
                     },
                 );
-            } else if let Some((x, y)) = movement_bottom_right.intersect(&walls_right[i]) {
+            } else if let Some((x, y)) = movement_bottom_right.intersect(wall) {
                 vb = VirtualBall::new(
                     x - self.width(),
                     y - self.height(),
@@ -275,7 +527,7 @@ impl Ball {
         step_y: i32,
     ) -> VirtualBall {
         let mut vb = VirtualBall::new(self.x() + step_x, self.y() + step_y, 0, 0, None);
-        for i in 0..walls_left.len() {
+        for (i, wall) in walls_left.iter().enumerate() {
             let movement_top_left = Segmet2D::new(self.x(), self.y(), vb.x(), vb.y());
             let movement_bottom_left = Segmet2D::new(
                 self.x(),
@@ -283,7 +535,7 @@ impl Ball {
                 vb.x(),
                 vb.y() + self.height(),
             );
-            if let Some((x, y)) = movement_top_left.intersect(&walls_left[i]) {
+            if let Some((x, y)) = movement_top_left.intersect(wall) {
                 vb = VirtualBall::new(
                     x,
                     y,
@@ -291,7 +543,7 @@ impl Ball {
                     (self.y() + step_y) - y,
                     if x - vb.x() != 0 { Some((id, i)) } else { None },
                 );
-            } else if let Some((x, y)) = movement_bottom_left.intersect(&walls_left[i]) {
+            } else if let Some((x, y)) = movement_bottom_left.intersect(wall) {
                 vb = VirtualBall::new(
                     x,
                     y - self.height(),
@@ -312,7 +564,7 @@ impl Ball {
         step_y: i32,
     ) -> VirtualBall {
         let mut vb = VirtualBall::new(self.x() + step_x, self.y() + step_y, 0, 0, None);
-        for i in 0..walls_up.len() {
+        for (i, wall) in walls_up.iter().enumerate() {
             let movement_top_left = Segmet2D::new(self.x(), self.y(), vb.x(), vb.y());
             let movement_top_right = Segmet2D::new(
                 self.x() + self.width(),
@@ -320,7 +572,7 @@ impl Ball {
                 vb.x() + self.width(),
                 vb.y(),
             );
-            if let Some((x, y)) = movement_top_left.intersect(&walls_up[i]) {
+            if let Some((x, y)) = movement_top_left.intersect(wall) {
                 vb = VirtualBall::new(
                     x,
                     y,
@@ -328,7 +580,7 @@ impl Ball {
                     y - (self.y() + step_y),
                     if y - vb.y() != 0 { Some((id, i)) } else { None },
                 );
-            } else if let Some((x, y)) = movement_top_right.intersect(&walls_up[i]) {
+            } else if let Some((x, y)) = movement_top_right.intersect(wall) {
                 vb = VirtualBall::new(
                     x - self.width(),
                     y,
@@ -349,7 +601,7 @@ impl Ball {
         step_y: i32,
     ) -> VirtualBall {
         let mut vb = VirtualBall::new(self.x() + step_x, self.y() + step_y, 0, 0, None);
-        for i in 0..walls_down.len() {
+        for (i, wall) in walls_down.iter().enumerate() {
             let movement_bottom_left = Segmet2D::new(
                 self.x(),
                 self.y() + self.height(),
@@ -362,7 +614,7 @@ impl Ball {
                 vb.x() + self.width(),
                 vb.y() + self.height(),
             );
-            if let Some((x, y)) = movement_bottom_left.intersect(&walls_down[i]) {
+            if let Some((x, y)) = movement_bottom_left.intersect(wall) {
                 vb = VirtualBall::new(
                     x,
                     y - self.height(),
@@ -374,7 +626,7 @@ impl Ball {
                         None
                     },
                 );
-            } else if let Some((x, y)) = movement_bottom_right.intersect(&walls_down[i]) {
+            } else if let Some((x, y)) = movement_bottom_right.intersect(wall) {
                 vb = VirtualBall::new(
                     x - self.width(),
                     y - self.height(),
@@ -391,14 +643,56 @@ impl Ball {
         vb
     }
 
-    fn build_walls(
-        &self,
-        paddles: Option<(&Paddle, &Paddle)>,
-        x_min: i32,
-        x_max: i32,
-        y_min: i32,
-        y_max: i32,
-    ) -> Vec<Vec<Segmet2D>> {
+    /// walls that are neither vertical nor horizontal, returns true if the ball bounced
+    fn bounce_diagonal(&mut self, walls: &[Segmet2D]) -> bool {
+        let radius = self.width() as f32 / 2.0;
+        let center = (self.x() as f32 + radius, self.y() as f32 + radius);
+        let (vx, vy) = (self.vx as f32, self.vy as f32);
+        let mut bounce = None;
+        for wall in walls
+            .iter()
+            .filter(|wall| !wall.is_vertical() && !wall.is_horizontal())
+        {
+            let (x1, y1) = wall.start();
+            let (x2, y2) = wall.end();
+            let (dx, dy) = ((x2 - x1) as f32, (y2 - y1) as f32);
+            let length = dx.hypot(dy);
+            let mut normal = (-dy / length, dx / length);
+            if (center.0 - x1 as f32) * normal.0 + (center.1 - y1 as f32) * normal.1 < 0.0 {
+                normal = (-normal.0, -normal.1);
+            }
+            if vx * normal.0 + vy * normal.1 >= 0.0 {
+                continue;
+            }
+            // the path of the centre against the wall moved towards the ball by the radius
+            let start = (x1 as f32 + normal.0 * radius, y1 as f32 + normal.1 * radius);
+            let denom = vx * dy - vy * dx;
+            let (ox, oy) = (start.0 - center.0, start.1 - center.1);
+            let t = (ox * dy - oy * dx) / denom;
+            let u = (ox * vy - oy * vx) / denom;
+            if !(0.0..=1.0).contains(&t) || !(0.0..=1.0).contains(&u) {
+                continue;
+            }
+            if bounce.is_none_or(|(closest, _)| t < closest) {
+                bounce = Some((t, normal));
+            }
+        }
+        let Some((t, normal)) = bounce else {
+            return false;
+        };
+        let point = (center.0 + vx * t, center.1 + vy * t);
+        let dot = vx * normal.0 + vy * normal.1;
+        self.vx = (vx - 2.0 * dot * normal.0).round() as i32;
+        self.vy = (vy - 2.0 * dot * normal.1).round() as i32;
+        self.rect.x = (point.0 + normal.0 - radius).round() as i32;
+        self.rect.y = (point.1 + normal.1 - radius).round() as i32;
+        true
+    }
+
+    /// the border, then the paddles, the obstacles, the bricks close to the ball and
+    /// the walls; returns the positions in `court.bricks` of the bricks used
+    fn build_walls(&self, paddles: &[&Paddle], court: &Court) -> (Vec<Vec<Segmet2D>>, Vec<usize>) {
+        let (x_min, x_max, y_min, y_max) = (court.x_min, court.x_max, court.y_min, court.y_max);
         let mut walls = vec![vec![]; 4];
         walls[0].push(Segmet2D::new(
             x_max,
@@ -424,56 +718,39 @@ impl Ball {
             x_max + self.vx().abs(),
             y_min,
         ));
-        if let Some((paddle_l, paddle_r)) = paddles {
-            walls[0].push(Segmet2D::new(
-                paddle_l.x(),
-                paddle_l.y(),
-                paddle_l.x(),
-                paddle_l.y() + paddle_l.height(),
-            ));
-            walls[0].push(Segmet2D::new(
-                paddle_r.x(),
-                paddle_r.y(),
-                paddle_r.x(),
-                paddle_r.y() + paddle_r.height(),
-            ));
-            walls[1].push(Segmet2D::new(
-                paddle_l.x() + paddle_l.width(),
-                paddle_l.y(),
-                paddle_l.x() + paddle_l.width(),
-                paddle_l.y() + paddle_l.height(),
-            ));
-            walls[1].push(Segmet2D::new(
-                paddle_r.x() + paddle_r.width(),
-                paddle_r.y(),
-                paddle_r.x() + paddle_r.width(),
-                paddle_r.y() + paddle_r.height(),
-            ));
-            walls[2].push(Segmet2D::new(
-                paddle_l.x(),
-                paddle_l.y(),
-                paddle_l.x() + paddle_l.width(),
-                paddle_l.y(),
-            ));
-            walls[2].push(Segmet2D::new(
-                paddle_r.x(),
-                paddle_r.y(),
-                paddle_r.x() + paddle_r.width(),
-                paddle_r.y(),
-            ));
-            walls[3].push(Segmet2D::new(
-                paddle_l.x(),
-                paddle_l.y() + paddle_l.height(),
-                paddle_l.x() + paddle_l.width(),
-                paddle_l.y() + paddle_l.height(),
-            ));
-            walls[3].push(Segmet2D::new(
-                paddle_r.x(),
-                paddle_r.y() + paddle_r.height(),
-                paddle_r.x() + paddle_r.width(),
-                paddle_r.y() + paddle_r.height(),
-            ));
+        // only the bricks the ball can reach during this tick
+        let reach = self.vx.abs() + self.vy.abs() + 1;
+        let bricks: Vec<_> = (0..court.bricks.len())
+            .filter(|i| {
+                let brick = court.bricks[*i].1;
+                brick.x() < self.x() + self.width() + reach
+                    && self.x() - reach < brick.right()
+                    && brick.y() < self.y() + self.height() + reach
+                    && self.y() - reach < brick.bottom()
+            })
+            .collect();
+        let rects = paddles
+            .iter()
+            .map(|paddle| paddle.rect())
+            .chain(court.rects.iter().copied())
+            .chain(bricks.iter().map(|i| court.bricks[*i].1));
+        for rect in rects {
+            let (left, top) = (rect.x(), rect.y());
+            let (right, bottom) = (left + rect.width() as i32, top + rect.height() as i32);
+            walls[0].push(Segmet2D::new(left, top, left, bottom));
+            walls[1].push(Segmet2D::new(right, top, right, bottom));
+            walls[2].push(Segmet2D::new(left, top, right, top));
+            walls[3].push(Segmet2D::new(left, bottom, right, bottom));
+        }
+        for wall in &court.walls {
+            if wall.is_vertical() {
+                walls[0].push(wall.clone());
+                walls[1].push(wall.clone());
+            } else if wall.is_horizontal() {
+                walls[2].push(wall.clone());
+                walls[3].push(wall.clone());
+            }
         }
-        walls
+        (walls, bricks)
     }
 }
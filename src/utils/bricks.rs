@@ -0,0 +1,150 @@
+use crate::constants::*;
+use crate::error::{InvalidLayout, InvalidPacket};
+use crate::packet::{PacketReader, PacketWriter};
+use crate::Result;
+use sdl2::rect::Rect;
+
+// the rows in the middle are left free for the serve
+const BUILTIN: [(&str, &str); 3] = [
+    ("wall", "11\n11\n11\n11\n11\n11\n11\n..\n..\n11\n11\n11\n11\n11\n11\n11\n"),
+    ("checker", "1.1\n.2.\n1.1\n.2.\n1.1\n.2.\n1.1\n...\n...\n.2.\n1.1\n.2.\n1.1\n.2.\n1.1\n.2.\n"),
+    ("fortress", "....\n....\n1111\n1221\n1231\n1231\n1231\n1..1\n1..1\n1231\n1231\n1231\n1221\n1111\n....\n....\n"),
+];
+
+/// a grid of bricks in the middle of the court, a brick breaks after some hits;
+/// layout files have one line for each row of `BRICK_HEIGHT`, with `.` for no brick
+/// and a digit for the hits a brick takes
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Bricks {
+    rects: Vec<Rect>,
+    /// hits each brick takes when the layout is filled
+    layout: Vec<u32>,
+    /// 0 once the brick is broken
+    hits: Vec<u32>,
+}
+
+impl Bricks {
+    pub fn builtin(name: &str) -> Option<Self> {
+        let (_, layout) = BUILTIN.iter().find(|(builtin, _)| *builtin == name)?;
+        Self::parse(layout).ok()
+    }
+
+    /// a built-in name or the path of a layout file
+    pub fn load(name: &str) -> Result<Self> {
+        match Self::builtin(name) {
+            Some(bricks) => Ok(bricks),
+            None => Self::parse(&std::fs::read_to_string(name)?),
+        }
+    }
+
+    pub fn parse(layout: &str) -> Result<Self> {
+        let rows: Vec<_> = layout
+            .lines()
+            .map(|line| line.split('#').next().unwrap_or_default().trim_end())
+            .filter(|line| !line.is_empty())
+            .collect();
+        let columns = rows.iter().map(|row| row.len()).max().unwrap_or(0) as i32;
+        // the layout has to fit in the court
+        if columns > (WINDOW_WIDTH / BRICK_WIDTH) as i32 {
+            let error = format!("more than {} columns", WINDOW_WIDTH / BRICK_WIDTH);
+            return Err(Box::new(InvalidLayout(error)));
+        }
+        if rows.len() > (WINDOW_HEIGHT / BRICK_HEIGHT) as usize {
+            let error = format!("more than {} rows", WINDOW_HEIGHT / BRICK_HEIGHT);
+            return Err(Box::new(InvalidLayout(error)));
+        }
+        let left = (WINDOW_WIDTH as i32 - columns * BRICK_WIDTH as i32) / 2;
+        let top = (WINDOW_HEIGHT as i32 - rows.len() as i32 * BRICK_HEIGHT as i32) / 2;
+        let mut bricks = Self::default();
+        for (i, row) in rows.iter().enumerate() {
+            for (j, cell) in row.chars().enumerate() {
+                let hits = match cell {
+                    '.' | ' ' => continue,
+                    digit => digit
+                        .to_digit(10)
+                        .filter(|hits| *hits > 0)
+                        .ok_or_else(|| InvalidLayout(format!("row {}: {}", i + 1, row)))?,
+                };
+                let x = left + j as i32 * BRICK_WIDTH as i32;
+                let y = top + i as i32 * BRICK_HEIGHT as i32;
+                // one pixel of mortar between the bricks
+                bricks
+                    .rects
+                    .push(Rect::new(x, y, BRICK_WIDTH - 1, BRICK_HEIGHT - 1));
+                bricks.layout.push(hits);
+            }
+        }
+        if bricks.layout.len() > MAX_BRICKS {
+            let error = format!("more than {} bricks", MAX_BRICKS);
+            return Err(Box::new(InvalidLayout(error)));
+        }
+        bricks.hits = bricks.layout.clone();
+        Ok(bricks)
+    }
+
+    /// every brick is back
+    pub fn reset(&mut self) {
+        self.hits = self.layout.clone();
+    }
+
+    /// the bricks not broken yet with their index and the hits they still take
+    pub fn alive(&self) -> impl Iterator<Item = (usize, Rect, u32)> + '_ {
+        self.rects
+            .iter()
+            .zip(&self.hits)
+            .enumerate()
+            .filter(|(_, (_, hits))| **hits > 0)
+            .map(|(i, (rect, hits))| (i, *rect, *hits))
+    }
+
+    /// true if the layout had bricks and all of them are broken
+    pub fn is_cleared(&self) -> bool {
+        !self.layout.is_empty() && self.hits.iter().all(|hits| *hits == 0)
+    }
+
+    /// returns the points given by the brick if it breaks
+    pub fn hit(&mut self, i: usize) -> Option<u32> {
+        let hits = self.hits.get_mut(i).filter(|hits| **hits > 0)?;
+        *hits -= 1;
+        (*hits == 0).then_some(self.layout[i])
+    }
+
+    pub fn write(&self, writer: &mut PacketWriter) {
+        writer.put_u32(self.hits.len() as u32);
+        for hits in &self.hits {
+            writer.put_u8(*hits as u8);
+        }
+    }
+
+    pub fn read(&mut self, reader: &mut PacketReader) -> Result<()> {
+        if reader.get_u32()? as usize != self.hits.len() {
+            return Err(Box::new(InvalidPacket));
+        }
+        for hits in &mut self.hits {
+            *hits = reader.get_u8()? as u32;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test() {
+    for (name, _) in BUILTIN {
+        assert!(Bricks::builtin(name).is_some());
+    }
+    let mut bricks = Bricks::parse("1.\n.2 # two hits").unwrap();
+    assert_eq!(bricks.alive().count(), 2);
+    assert_eq!(bricks.hit(0), Some(1));
+    assert_eq!(bricks.hit(0), None);
+    assert_eq!(bricks.hit(1), None);
+    assert_eq!(bricks.hit(1), Some(2));
+    assert!(bricks.is_cleared());
+    bricks.reset();
+    assert_eq!(bricks.alive().count(), 2);
+    assert!(Bricks::parse("1x").is_err());
+    let row = "1".repeat((WINDOW_WIDTH / BRICK_WIDTH) as usize);
+    let rows = (WINDOW_HEIGHT / BRICK_HEIGHT) as usize;
+    assert!(Bricks::parse(&vec![row.as_str(); rows].join("\n")).is_ok());
+    assert!(Bricks::parse(&vec![row.as_str(); rows + 1].join("\n")).is_err());
+    assert!(Bricks::parse(&format!("{row}1")).is_err());
+}
@@ -0,0 +1,393 @@
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::constants::*;
+use crate::error::InvalidArgument;
+use crate::Result;
+
+/// the formats a frame can be written to, given by the extension of the file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    Bmp,
+    Gif,
+}
+
+impl ImageFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "png" => Some(ImageFormat::Png),
+            "bmp" => Some(ImageFormat::Bmp),
+            "gif" => Some(ImageFormat::Gif),
+            _ => None,
+        }
+    }
+}
+
+/// a frame, 3 bytes for each pixel from the top left corner
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub rgb: Vec<u8>,
+}
+
+impl Image {
+    /// what has been drawn on `canvas` and not presented yet, the whole window
+    /// with the black bars around the court
+    pub fn read(canvas: &mut Canvas<Window>) -> Result<Self> {
+        // without the logical size the pixels read are not limited to the court
+        let (logical_width, logical_height) = canvas.logical_size();
+        canvas.set_logical_size(0, 0)?;
+        let (width, height) = canvas.output_size()?;
+        let rgb = canvas.read_pixels(None, PixelFormatEnum::RGB24);
+        canvas.set_logical_size(logical_width, logical_height)?;
+        Ok(Self {
+            width,
+            height,
+            rgb: rgb?,
+        })
+    }
+
+    /// covers the whole window, black bars included, so it has the size of the window
+    pub fn draw(&self, canvas: &mut Canvas<Window>) -> Result<()> {
+        let texture_creator = canvas.texture_creator();
+        let mut texture = texture_creator.create_texture_streaming(
+            PixelFormatEnum::RGB24,
+            self.width,
+            self.height,
+        )?;
+        texture.update(None, &self.rgb, self.width as usize * 3)?;
+        let (logical_width, logical_height) = canvas.logical_size();
+        canvas.set_logical_size(0, 0)?;
+        let copied = canvas.copy(&texture, None, None);
+        canvas.set_logical_size(logical_width, logical_height)?;
+        Ok(copied?)
+    }
+
+    pub fn encode(&self, format: ImageFormat) -> Vec<u8> {
+        match format {
+            ImageFormat::Png => self.png(),
+            ImageFormat::Bmp => self.bmp(),
+            ImageFormat::Gif => {
+                let mut bytes = gif_header(self.width, self.height);
+                bytes.extend(self.gif_frame(0));
+                bytes.push(0x3b);
+                bytes
+            }
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let format = ImageFormat::from_path(path)
+            .ok_or_else(|| InvalidArgument(path.display().to_string()))?;
+        std::fs::write(path, self.encode(format))?;
+        Ok(())
+    }
+
+    fn rows(&self) -> impl DoubleEndedIterator<Item = &[u8]> {
+        self.rgb.chunks(self.width as usize * 3)
+    }
+
+    /// not compressed, the deflate stream is made of stored blocks
+    fn png(&self) -> Vec<u8> {
+        let mut raw = Vec::with_capacity(self.rgb.len() + self.height as usize);
+        for row in self.rows() {
+            // no filter
+            raw.push(0);
+            raw.extend_from_slice(row);
+        }
+        let mut zlib = vec![0x78, 0x01];
+        let blocks = raw.chunks(u16::MAX as usize);
+        let last = blocks.len().saturating_sub(1);
+        for (i, block) in blocks.enumerate() {
+            zlib.push((i == last) as u8);
+            zlib.extend((block.len() as u16).to_le_bytes());
+            zlib.extend((!(block.len() as u16)).to_le_bytes());
+            zlib.extend_from_slice(block);
+        }
+        zlib.extend(adler32(&raw).to_be_bytes());
+
+        let mut header = vec![];
+        header.extend(self.width.to_be_bytes());
+        header.extend(self.height.to_be_bytes());
+        // 8 bits for each channel, rgb, no interlace
+        header.extend([8, 2, 0, 0, 0]);
+        let mut png = vec![0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+        for (kind, data) in [(b"IHDR", &header), (b"IDAT", &zlib), (b"IEND", &vec![])] {
+            png.extend((data.len() as u32).to_be_bytes());
+            let start = png.len();
+            png.extend_from_slice(kind);
+            png.extend_from_slice(data);
+            let crc = crc32(&png[start..]);
+            png.extend(crc.to_be_bytes());
+        }
+        png
+    }
+
+    /// 24 bits, the rows go from the bottom and are padded to 4 bytes
+    fn bmp(&self) -> Vec<u8> {
+        let row_size = (self.width as usize * 3).next_multiple_of(4);
+        let size = 54 + row_size * self.height as usize;
+        let mut bmp = vec![b'B', b'M'];
+        bmp.extend((size as u32).to_le_bytes());
+        bmp.extend(0u32.to_le_bytes());
+        bmp.extend(54u32.to_le_bytes());
+        bmp.extend(40u32.to_le_bytes());
+        bmp.extend(self.width.to_le_bytes());
+        bmp.extend(self.height.to_le_bytes());
+        bmp.extend(1u16.to_le_bytes());
+        bmp.extend(24u16.to_le_bytes());
+        bmp.extend([0; 24]);
+        for row in self.rows().rev() {
+            let start = bmp.len();
+            for pixel in row.chunks(3) {
+                bmp.extend([pixel[2], pixel[1], pixel[0]]);
+            }
+            bmp.resize(start + row_size, 0);
+        }
+        bmp
+    }
+
+    /// one image of an animated gif, shown for `delay` hundredths of a second;
+    /// the colors are rounded to the palette of `gif_header`
+    fn gif_frame(&self, delay: u16) -> Vec<u8> {
+        let mut gif = vec![0x21, 0xf9, 4, 0];
+        gif.extend(delay.to_le_bytes());
+        gif.extend([0, 0, 0x2c, 0, 0, 0, 0]);
+        gif.extend((self.width as u16).to_le_bytes());
+        gif.extend((self.height as u16).to_le_bytes());
+        gif.extend([0, 8]);
+        let level = |channel: u8| (channel as u32 * 5 + 127) / 255;
+        let indexes = self
+            .rgb
+            .chunks(3)
+            .map(|pixel| (level(pixel[0]) * 36 + level(pixel[1]) * 6 + level(pixel[2])) as u16);
+        let data = lzw(indexes);
+        for block in data.chunks(255) {
+            gif.push(block.len() as u8);
+            gif.extend_from_slice(block);
+        }
+        gif.push(0);
+        gif
+    }
+}
+
+/// the screen and a palette of 6 levels for each channel, the animation loops
+fn gif_header(width: u32, height: u32) -> Vec<u8> {
+    let mut gif = b"GIF89a".to_vec();
+    gif.extend((width as u16).to_le_bytes());
+    gif.extend((height as u16).to_le_bytes());
+    gif.extend([0xf7, 0, 0]);
+    for i in 0..256u32 {
+        let (r, g, b) = if i < 216 {
+            (i / 36, i / 6 % 6, i % 6)
+        } else {
+            (0, 0, 0)
+        };
+        gif.extend([r, g, b].map(|level| (level * 51) as u8));
+    }
+    gif.extend(b"\x21\xff\x0bNETSCAPE2.0\x03\x01\x00\x00\x00");
+    gif
+}
+
+/// every pixel is a code of its own: the table is cleared before it needs codes
+/// longer than 9 bits, so there is nothing to look up
+fn lzw(indexes: impl Iterator<Item = u16>) -> Vec<u8> {
+    const CLEAR: u16 = 256;
+    const END: u16 = 257;
+    let mut bytes = vec![];
+    let (mut buffer, mut bits) = (0u32, 0);
+    let mut put = |code: u16, bytes: &mut Vec<u8>| {
+        buffer |= (code as u32) << bits;
+        bits += 9;
+        while bits >= 8 {
+            bytes.push(buffer as u8);
+            buffer >>= 8;
+            bits -= 8;
+        }
+    };
+    for (i, index) in indexes.enumerate() {
+        if i % 250 == 0 {
+            put(CLEAR, &mut bytes);
+        }
+        put(index, &mut bytes);
+    }
+    put(END, &mut bytes);
+    if bits > 0 {
+        bytes.push(buffer as u8);
+    }
+    bytes
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in bytes {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for byte in bytes {
+        a = (a + *byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+/// frames written while recording, an animated gif or one numbered file for each
+struct Recording {
+    path: PathBuf,
+    /// one frame out of `every` is written
+    every: u32,
+    written: u32,
+    gif: Option<BufWriter<File>>,
+}
+
+/// screenshots and recordings of the frames drawn
+#[derive(Default)]
+pub struct Capture {
+    /// frames drawn so far
+    frames: u32,
+    /// the game stops after this many frames
+    limit: Option<u32>,
+    /// where the last frame is written when the game stops
+    last: Option<PathBuf>,
+    /// written with the next frame
+    screenshots: Vec<PathBuf>,
+    recording: Option<Recording>,
+}
+
+impl Capture {
+    pub fn new(limit: Option<u32>, last: Option<PathBuf>) -> Self {
+        Self {
+            limit,
+            last,
+            ..Default::default()
+        }
+    }
+
+    /// a gif file gets every frame, otherwise the number of the frame is added to
+    /// the name of the file
+    pub fn record(&mut self, path: PathBuf, every: u32) -> Result<()> {
+        ImageFormat::from_path(&path).ok_or_else(|| InvalidArgument(path.display().to_string()))?;
+        self.recording = Some(Recording {
+            path,
+            every: every.max(1),
+            written: 0,
+            gif: None,
+        });
+        Ok(())
+    }
+
+    /// the next frame is written to the first `SCREENSHOT_FILE` not taken yet
+    pub fn screenshot(&mut self) {
+        let path = (1..)
+            .map(|i| PathBuf::from(format!("{SCREENSHOT_FILE}_{i:03}.png")))
+            .find(|path| !path.exists() && !self.screenshots.contains(path))
+            .expect("It can't be None");
+        self.screenshots.push(path);
+    }
+
+    /// true once `limit` frames are drawn
+    pub fn is_done(&self) -> bool {
+        self.limit.is_some_and(|limit| self.frames >= limit)
+    }
+
+    /// the frame drawn on `canvas` before it is presented
+    pub fn frame(&mut self, canvas: &mut Canvas<Window>) -> Result<()> {
+        self.frames += 1;
+        if self.is_done() {
+            self.screenshots.extend(self.last.take());
+        }
+        let record = self
+            .recording
+            .as_ref()
+            .is_some_and(|recording| (self.frames - 1).is_multiple_of(recording.every));
+        if !record && self.screenshots.is_empty() {
+            return Ok(());
+        }
+        let image = Image::read(canvas)?;
+        for path in self.screenshots.drain(..) {
+            image.save(&path)?;
+        }
+        match &mut self.recording {
+            Some(recording) if record => recording.write(&image),
+            _ => Ok(()),
+        }
+    }
+
+    /// closes the recording, returns true if the last frame is still to be written:
+    /// it is written with the next frame drawn
+    pub fn stop(&mut self) -> Result<bool> {
+        if let Some(mut gif) = self.recording.take().and_then(|recording| recording.gif) {
+            gif.write_all(&[0x3b])?;
+            gif.flush()?;
+        }
+        self.screenshots.extend(self.last.take());
+        Ok(!self.screenshots.is_empty())
+    }
+}
+
+impl Recording {
+    fn write(&mut self, image: &Image) -> Result<()> {
+        self.written += 1;
+        if ImageFormat::from_path(&self.path) != Some(ImageFormat::Gif) {
+            let stem = self.path.file_stem().unwrap_or_default().to_string_lossy();
+            let extension = self.path.extension().unwrap_or_default().to_string_lossy();
+            let name = format!("{stem}_{:05}.{extension}", self.written);
+            return image.save(&self.path.with_file_name(name));
+        }
+        let gif = match &mut self.gif {
+            Some(gif) => gif,
+            None => {
+                let mut gif = BufWriter::new(File::create(&self.path)?);
+                gif.write_all(&gif_header(image.width, image.height))?;
+                self.gif.insert(gif)
+            }
+        };
+        let delay = (self.every as u64 * 100 / FPS).max(2) as u16;
+        gif.write_all(&image.gif_frame(delay))?;
+        Ok(())
+    }
+}
+
+#[test]
+fn test() {
+    assert_eq!(crc32(b"IEND"), 0xae42_6082);
+    assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+    let image = Image {
+        width: 3,
+        height: 2,
+        rgb: (0..18).map(|i| i * 15).collect(),
+    };
+    let png = image.encode(ImageFormat::Png);
+    assert_eq!(&png[1..4], b"PNG");
+    assert_eq!(&png[png.len() - 8..png.len() - 4], b"IEND");
+    let bmp = image.encode(ImageFormat::Bmp);
+    assert_eq!(bmp.len(), 54 + 12 * 2);
+    // the bottom row comes first, blue first
+    assert_eq!(&bmp[54..57], &[15 * 11, 15 * 10, 15 * 9]);
+    let gif = image.encode(ImageFormat::Gif);
+    assert_eq!(&gif[..6], b"GIF89a");
+    assert_eq!(gif.last(), Some(&0x3b));
+    assert_eq!(
+        ImageFormat::from_path(Path::new("shot.PNG")),
+        Some(ImageFormat::Png)
+    );
+    assert_eq!(ImageFormat::from_path(Path::new("shot.jpg")), None);
+}
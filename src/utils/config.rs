@@ -0,0 +1,396 @@
+use crate::arena::Arena;
+use crate::bricks::Bricks;
+use crate::capture::ImageFormat;
+use crate::constants::*;
+use crate::error::InvalidArgument;
+use crate::mode::Mode;
+use crate::net::NetConfig;
+use crate::powerup::PowerUpKind;
+use crate::rules::{Rules, Serve};
+use crate::theme::Theme;
+use crate::world::{MultiBall, RallySpeed};
+use crate::Result;
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NetRole {
+    Host(u16),
+    Join(String),
+}
+
+/// options given on the command line, everything else is in `constants`
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub net: Option<NetRole>,
+    pub net_config: NetConfig,
+    pub rollback: bool,
+    pub broadcast: Option<u16>,
+    pub spectate: Option<String>,
+    pub mode: Mode,
+    /// paddles driven by the cpu, numbered from 1
+    pub cpu: Vec<usize>,
+    pub multi_ball: MultiBall,
+    pub rally_speed: RallySpeed,
+    pub power_ups: Vec<PowerUpKind>,
+    pub arena: Arena,
+    pub bricks: Bricks,
+    pub rules: Rules,
+    /// in ticks, `None` serves the ball from the centre after a goal
+    pub serve_timeout: Option<u32>,
+    pub spin: bool,
+    /// starts with the debug overlay shown
+    pub debug: bool,
+    /// where the last frame is written
+    pub screenshot: Option<PathBuf>,
+    pub record: Option<PathBuf>,
+    /// one frame out of `record_every` is recorded
+    pub record_every: u32,
+    /// the game stops after this many frames
+    pub frames: Option<u32>,
+    /// no window is shown and the frames are drawn by the software renderer
+    pub headless: bool,
+    /// plays in the terminal instead of a window
+    pub terminal: bool,
+    pub fullscreen: bool,
+    pub theme: Theme,
+    /// no particles, trail, shake, flash or growing score
+    pub reduced_motion: bool,
+    /// scanlines, bloom, curvature and vignette drawn over each frame
+    pub crt: bool,
+    /// pauses when the window loses the focus or is minimized
+    pub auto_pause: bool,
+    /// in ticks, frozen before playing again after a pause
+    pub resume_countdown: u32,
+    /// in ticks, the balls are frozen before each serve
+    pub countdown: u32,
+    /// no beeps
+    pub mute: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            net: None,
+            net_config: NetConfig {
+                delay: NET_DELAY,
+                latency: Duration::ZERO,
+                loss: 0.0,
+                timeout: Duration::from_secs(NET_TIMEOUT),
+            },
+            rollback: false,
+            broadcast: None,
+            spectate: None,
+            mode: Mode::Classic,
+            cpu: vec![],
+            multi_ball: MultiBall::default(),
+            rally_speed: RallySpeed::default(),
+            power_ups: vec![],
+            arena: Arena::default(),
+            bricks: Bricks::default(),
+            rules: Rules::default(),
+            serve_timeout: None,
+            spin: false,
+            debug: false,
+            screenshot: None,
+            record: None,
+            record_every: 1,
+            frames: None,
+            headless: false,
+            terminal: false,
+            fullscreen: false,
+            theme: Theme::default(),
+            reduced_motion: false,
+            crt: false,
+            auto_pause: true,
+            resume_countdown: 0,
+            countdown: 0,
+            mute: false,
+        }
+    }
+}
+
+impl Config {
+    /// `args` must not contain the program name
+    pub fn from_args(mut args: impl Iterator<Item = String>) -> Result<Self> {
+        let mut config = Self::default();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| InvalidArgument(arg.clone()));
+            match arg.as_str() {
+                "--host" => config.net = Some(NetRole::Host(value()?.parse()?)),
+                "--join" => config.net = Some(NetRole::Join(value()?)),
+                "--rollback" => config.rollback = true,
+                "--broadcast" => config.broadcast = Some(value()?.parse()?),
+                "--spectate" => config.spectate = Some(value()?),
+                "--cpu" => {
+                    for paddle in value()?.split(',') {
+                        config.cpu.push(paddle.parse()?);
+                    }
+                }
+                "--balls" => {
+                    config.multi_ball.count = value()?.parse()?;
+                    if config.multi_ball.count == 0 {
+                        return Err(Box::new(InvalidArgument(arg)));
+                    }
+                }
+                "--spawn" => {
+                    let seconds: u32 = value()?.parse()?;
+                    config.multi_ball.spawn = Some(ticks(seconds, &arg)?.max(1));
+                }
+                "--ball-collisions" => config.multi_ball.collisions = true,
+                "--rally-speed" => config.rally_speed.per_hit = value()?.parse()?,
+                "--rally-tiers" => {
+                    config.rally_speed.tiers.clear();
+                    for tier in value()?.split(',') {
+                        let (hits, percent) = tier
+                            .split_once(':')
+                            .ok_or(InvalidArgument(tier.to_string()))?;
+                        config
+                            .rally_speed
+                            .tiers
+                            .push((hits.parse()?, percent.parse()?));
+                    }
+                }
+                "--rally-cap" => config.rally_speed.cap = value()?.parse()?,
+                "--power-ups" => {
+                    config.power_ups.clear();
+                    for name in value()?.split(',') {
+                        match (name, PowerUpKind::from_name(name)) {
+                            ("all", _) => config.power_ups.extend(PowerUpKind::ALL),
+                            (_, Some(kind)) => config.power_ups.push(kind),
+                            (_, None) => return Err(Box::new(InvalidArgument(name.to_string()))),
+                        }
+                    }
+                }
+                "--points" => config.rules.points_to_win = value()?.parse()?,
+                "--win-by-two" => config.rules.win_by = 2,
+                "--time" => {
+                    let seconds: u32 = value()?.parse()?;
+                    config.rules.time_limit = Some(ticks(seconds, &arg)?);
+                }
+                "--sets" => {
+                    config.rules.sets = value()?.parse()?;
+                    if config.rules.sets.is_multiple_of(2) {
+                        return Err(Box::new(InvalidArgument(arg)));
+                    }
+                }
+                "--serve" => {
+                    let name = value()?;
+                    config.rules.serve = Serve::from_name(&name).ok_or(InvalidArgument(name))?;
+                }
+                "--spin" => config.spin = true,
+                "--debug" => config.debug = true,
+                "--screenshot" => config.screenshot = Some(PathBuf::from(value()?)),
+                "--record" => config.record = Some(PathBuf::from(value()?)),
+                "--record-every" => config.record_every = value()?.parse()?,
+                "--frames" => config.frames = Some(value()?.parse()?),
+                "--headless" => config.headless = true,
+                "--terminal" => config.terminal = true,
+                "--fullscreen" => config.fullscreen = true,
+                "--theme" => config.theme = Theme::load(&value()?)?,
+                "--reduced-motion" => config.reduced_motion = true,
+                "--crt" => config.crt = true,
+                "--no-auto-pause" => config.auto_pause = false,
+                "--countdown" => {
+                    let seconds: u32 = value()?.parse()?;
+                    config.countdown = ticks(seconds, &arg)?;
+                }
+                "--mute" => config.mute = true,
+                "--resume-countdown" => {
+                    let seconds: u32 = value()?.parse()?;
+                    config.resume_countdown = ticks(seconds, &arg)?;
+                }
+                "--player-serve" => config.serve_timeout = Some(SERVE_TIMEOUT as u32 * FPS as u32),
+                "--serve-timeout" => {
+                    let seconds: u32 = value()?.parse()?;
+                    config.serve_timeout = Some(ticks(seconds, &arg)?.max(1));
+                }
+                "--arena" => config.arena = Arena::load(&value()?)?,
+                "--bricks" => config.bricks = Bricks::load(&value()?)?,
+                "--mode" => {
+                    let name = value()?;
+                    config.mode = Mode::from_name(&name).ok_or(InvalidArgument(name))?;
+                }
+                "--delay" => config.net_config.delay = value()?.parse()?,
+                "--latency" => config.net_config.latency = Duration::from_millis(value()?.parse()?),
+                "--loss" => {
+                    let loss: f64 = value()?.parse()?;
+                    if !(0.0..=100.0).contains(&loss) {
+                        return Err(Box::new(InvalidArgument(arg)));
+                    }
+                    config.net_config.loss = loss / 100.0;
+                }
+                _ => return Err(Box::new(InvalidArgument(arg))),
+            }
+        }
+        for (flag, path) in [
+            ("--screenshot", &config.screenshot),
+            ("--record", &config.record),
+        ] {
+            if path
+                .as_ref()
+                .is_some_and(|path| ImageFormat::from_path(path).is_none())
+            {
+                return Err(Box::new(InvalidArgument(flag.to_string())));
+            }
+        }
+        // nobody could close the game
+        if config.headless && config.frames.is_none() {
+            return Err(Box::new(InvalidArgument("--headless".to_string())));
+        }
+        if config.terminal && (config.net.is_some() || config.spectate.is_some()) {
+            return Err(Box::new(InvalidArgument("--terminal".to_string())));
+        }
+        // the network code only knows about two players and a single ball
+        if config.net.is_some() && config.mode != Mode::Classic {
+            return Err(Box::new(InvalidArgument("--mode".to_string())));
+        }
+        if config.net.is_some() && config.multi_ball != MultiBall::default() {
+            return Err(Box::new(InvalidArgument("--balls".to_string())));
+        }
+        if config.net.is_some() && !config.power_ups.is_empty() {
+            return Err(Box::new(InvalidArgument("--power-ups".to_string())));
+        }
+        if config.net.is_some() && config.arena != Arena::default() {
+            return Err(Box::new(InvalidArgument("--arena".to_string())));
+        }
+        if config.net.is_some() && config.bricks != Bricks::default() {
+            return Err(Box::new(InvalidArgument("--bricks".to_string())));
+        }
+        let (rules, default) = (config.rules, Rules::default());
+        let changed = [
+            ("--points", rules.points_to_win != default.points_to_win),
+            ("--win-by-two", rules.win_by != default.win_by),
+            ("--time", rules.time_limit != default.time_limit),
+            ("--sets", rules.sets != default.sets),
+            ("--serve", rules.serve != default.serve),
+        ];
+        let changed = changed.iter().find(|(_, changed)| *changed);
+        if let (Some(_), Some((flag, _))) = (&config.net, changed) {
+            return Err(Box::new(InvalidArgument(flag.to_string())));
+        }
+        if config.net.is_some() && config.serve_timeout.is_some() {
+            return Err(Box::new(InvalidArgument("--player-serve".to_string())));
+        }
+        if config.net.is_some() && config.rally_speed != RallySpeed::default() {
+            return Err(Box::new(InvalidArgument("--rally-speed".to_string())));
+        }
+        if config.net.is_some() && config.spin {
+            return Err(Box::new(InvalidArgument("--spin".to_string())));
+        }
+        if config.net.is_some() && config.countdown > 0 {
+            return Err(Box::new(InvalidArgument("--countdown".to_string())));
+        }
+        Ok(config)
+    }
+}
+
+/// `seconds` in ticks, an invalid `arg` when it doesn't fit
+fn ticks(seconds: u32, arg: &str) -> Result<u32> {
+    Ok(seconds
+        .checked_mul(FPS as u32)
+        .ok_or_else(|| InvalidArgument(arg.to_string()))?)
+}
+
+#[test]
+fn test() {
+    let args = "--join 127.0.0.1:4000 --latency 80 --loss 5";
+    let config = Config::from_args(args.split(' ').map(String::from)).unwrap();
+    assert_eq!(
+        config.net,
+        Some(NetRole::Join("127.0.0.1:4000".to_string()))
+    );
+    assert_eq!(config.net_config.latency, Duration::from_millis(80));
+    assert_eq!(config.net_config.loss, 0.05);
+    assert!(Config::from_args(["--host".to_string()].into_iter()).is_err());
+    assert!(Config::from_args(["--loss".to_string(), "200".to_string()].into_iter()).is_err());
+    let args = "--mode doubles --cpu 2,4";
+    let config = Config::from_args(args.split(' ').map(String::from)).unwrap();
+    assert_eq!((config.mode, config.cpu), (Mode::Doubles, vec![2, 4]));
+    let args = "--balls 3 --spawn 2 --ball-collisions";
+    let config = Config::from_args(args.split(' ').map(String::from)).unwrap();
+    assert_eq!(config.multi_ball.count, 3);
+    assert_eq!(config.multi_ball.spawn, Some(2 * FPS as u32));
+    assert!(config.multi_ball.collisions);
+    assert!(Config::from_args(["--spawn".to_string(), u32::MAX.to_string()].into_iter()).is_err());
+    let args = "--power-ups grow,invert";
+    let config = Config::from_args(args.split(' ').map(String::from)).unwrap();
+    assert_eq!(config.power_ups, [PowerUpKind::Grow, PowerUpKind::Invert]);
+    assert!(Config::from_args(["--power-ups".to_string(), "fly".to_string()].into_iter()).is_err());
+    let args = "--arena pillars";
+    let config = Config::from_args(args.split(' ').map(String::from)).unwrap();
+    assert_eq!(Some(config.arena), Arena::builtin("pillars"));
+    assert!(
+        Config::from_args(["--arena".to_string(), "/nowhere".to_string()].into_iter()).is_err()
+    );
+    let args = "--bricks checker";
+    let config = Config::from_args(args.split(' ').map(String::from)).unwrap();
+    assert_eq!(Some(config.bricks), Bricks::builtin("checker"));
+    let args = "--headless --frames 600 --screenshot end.png --record clip.gif --record-every 2";
+    let config = Config::from_args(args.split(' ').map(String::from)).unwrap();
+    assert_eq!(config.record, Some(PathBuf::from("clip.gif")));
+    assert_eq!((config.frames, config.record_every), (Some(600), 2));
+    assert!(Config::from_args(["--headless".to_string()].into_iter()).is_err());
+    let args = "--screenshot end.jpg";
+    assert!(Config::from_args(args.split(' ').map(String::from)).is_err());
+    let args = "--theme amber";
+    let config = Config::from_args(args.split(' ').map(String::from)).unwrap();
+    assert_eq!(Some(config.theme), Theme::builtin("amber"));
+    assert!(!config.reduced_motion);
+    let args = "--reduced-motion";
+    assert!(
+        Config::from_args(args.split(' ').map(String::from))
+            .unwrap()
+            .reduced_motion
+    );
+    let args = "--crt --theme green";
+    let config = Config::from_args(args.split(' ').map(String::from)).unwrap();
+    assert!(config.crt && !config.reduced_motion);
+    assert!(config.auto_pause);
+    let args = "--no-auto-pause --resume-countdown 3";
+    let config = Config::from_args(args.split(' ').map(String::from)).unwrap();
+    assert_eq!(
+        (config.auto_pause, config.resume_countdown),
+        (false, 3 * FPS as u32)
+    );
+    let args = ["--resume-countdown".to_string(), u32::MAX.to_string()];
+    assert!(Config::from_args(args.into_iter()).is_err());
+    let args = "--countdown 3 --mute";
+    let config = Config::from_args(args.split(' ').map(String::from)).unwrap();
+    assert_eq!((config.countdown, config.mute), (3 * FPS as u32, true));
+    let args = ["--countdown".to_string(), u32::MAX.to_string()];
+    assert!(Config::from_args(args.into_iter()).is_err());
+    let args = "--host 4000 --countdown 3";
+    assert!(Config::from_args(args.split(' ').map(String::from)).is_err());
+    let args = "--points 11 --win-by-two --time 90 --sets 3 --serve alternate:2";
+    let config = Config::from_args(args.split(' ').map(String::from)).unwrap();
+    let rules = Rules {
+        points_to_win: 11,
+        win_by: 2,
+        time_limit: Some(90 * FPS as u32),
+        sets: 3,
+        serve: Serve::Alternate(2),
+    };
+    assert_eq!(config.rules, rules);
+    assert!(Config::from_args(["--time".to_string(), u32::MAX.to_string()].into_iter()).is_err());
+    assert!(Config::from_args(["--sets".to_string(), "2".to_string()].into_iter()).is_err());
+    // the flag given is the one reported
+    let args = "--host 4000 --win-by-two";
+    let error = Config::from_args(args.split(' ').map(String::from)).unwrap_err();
+    assert!(error.to_string().ends_with("--win-by-two"));
+    let args = "--player-serve";
+    let config = Config::from_args(args.split(' ').map(String::from)).unwrap();
+    assert_eq!(
+        config.serve_timeout,
+        Some(SERVE_TIMEOUT as u32 * FPS as u32)
+    );
+    let args = ["--serve-timeout".to_string(), u32::MAX.to_string()];
+    assert!(Config::from_args(args.into_iter()).is_err());
+    let args = "--rally-speed 5 --rally-tiers 4:20,12:20 --rally-cap 250";
+    let config = Config::from_args(args.split(' ').map(String::from)).unwrap();
+    assert_eq!(config.rally_speed.tiers, [(4, 20), (12, 20)]);
+    assert_eq!(config.rally_speed.factor(4), 1.4);
+    assert_eq!(config.rally_speed.factor(30), 2.5);
+    let args = "--mode four --host 4000";
+    assert!(Config::from_args(args.split(' ').map(String::from)).is_err());
+}
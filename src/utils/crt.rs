@@ -0,0 +1,216 @@
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+use std::time::{Duration, Instant};
+
+use crate::capture::Image;
+use crate::constants::*;
+use crate::Result;
+
+/// an old screen drawn on the CPU over a frame read back from the window: the
+/// picture bulges, gets darker towards the corners and on every other line, and
+/// bright pixels glow on their neighbours along the line; the tables only change
+/// with the size of the window
+#[derive(Debug, Clone)]
+pub struct Crt {
+    width: u32,
+    height: u32,
+    /// for each pixel the one it shows and the light kept by the vignette and the
+    /// scanlines, out of 256, none outside of the curved screen
+    pixels: Vec<(u32, u32)>,
+    bloom: bool,
+    /// frames over `CRT_BUDGET_MS` in a row
+    slow: u32,
+    glow: Vec<u8>,
+    rgb: Vec<u8>,
+}
+
+impl Default for Crt {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Crt {
+    pub fn new() -> Self {
+        Self {
+            width: 0,
+            height: 0,
+            pixels: vec![],
+            bloom: true,
+            slow: 0,
+            glow: vec![],
+            rgb: vec![],
+        }
+    }
+
+    pub fn has_bloom(&self) -> bool {
+        self.bloom
+    }
+
+    /// replaces what has been drawn on `canvas` and not presented yet
+    pub fn draw(&mut self, canvas: &mut Canvas<Window>) -> Result<()> {
+        let start = Instant::now();
+        let mut frame = Image::read(canvas)?;
+        self.apply(&mut frame);
+        frame.draw(canvas)?;
+        self.record(start.elapsed());
+        Ok(())
+    }
+
+    pub fn apply(&mut self, image: &mut Image) {
+        if (image.width, image.height) != (self.width, self.height) {
+            self.resize(image.width, image.height);
+        }
+        let lit = if self.bloom {
+            self.glow(&image.rgb);
+            &self.glow
+        } else {
+            &image.rgb
+        };
+        self.rgb.resize(image.rgb.len(), 0);
+        for (pixel, (source, shade)) in self.rgb.chunks_exact_mut(3).zip(&self.pixels) {
+            let source = *source as usize * 3;
+            for (value, lit) in pixel.iter_mut().zip(&lit[source..source + 3]) {
+                *value = (*lit as u32 * shade / 256) as u8;
+            }
+        }
+        std::mem::swap(&mut image.rgb, &mut self.rgb);
+    }
+
+    /// the bloom is dropped once the frames keep taking too long, so that the
+    /// game keeps its speed
+    pub fn record(&mut self, spent: Duration) {
+        if spent > Duration::from_millis(CRT_BUDGET_MS) {
+            self.slow += 1;
+        } else {
+            self.slow = 0;
+        }
+        if self.slow >= CRT_SLOW_FRAMES {
+            self.bloom = false;
+        }
+    }
+
+    fn resize(&mut self, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+        self.pixels.clear();
+        let (w, h) = (width as f32, height as f32);
+        for y in 0..height {
+            // every other line of the court, whatever the size of the window
+            let scanline = (y * WINDOW_HEIGHT / height) % 2 == 1;
+            for x in 0..width {
+                let u = (x as f32 + 0.5) / w * 2.0 - 1.0;
+                let v = (y as f32 + 0.5) / h * 2.0 - 1.0;
+                let distance = u * u + v * v;
+                let bend = 1.0 + CRT_CURVATURE * distance;
+                let (u, v) = (u * bend, v * bend);
+                if u.abs() >= 1.0 || v.abs() >= 1.0 {
+                    self.pixels.push((0, 0));
+                    continue;
+                }
+                let x = (((u + 1.0) / 2.0 * w) as u32).min(width - 1);
+                let y = (((v + 1.0) / 2.0 * h) as u32).min(height - 1);
+                let mut shade = 1.0 - CRT_VIGNETTE * distance / 2.0;
+                if scanline {
+                    shade *= CRT_SCANLINE;
+                }
+                self.pixels
+                    .push((y * width + x, (shade.max(0.0) * 256.0).round() as u32));
+            }
+        }
+    }
+
+    /// each pixel with a part of the average of its neighbours on the line, which
+    /// continues in black on both sides
+    fn glow(&mut self, rgb: &[u8]) {
+        let radius = CRT_BLOOM_RADIUS;
+        let window = 2 * radius as u32 + 1;
+        let row = self.width as usize * 3;
+        self.glow.resize(rgb.len(), 0);
+        for (line, glow) in rgb.chunks(row).zip(self.glow.chunks_mut(row)) {
+            // the sums of the channels of the window around the pixel lit, from the
+            // pixel behind it to the one ahead of it
+            let mut sums = [0u32; 3];
+            let add = |sums: &mut [u32; 3], pixel: &[u8]| {
+                sums.iter_mut()
+                    .zip(pixel)
+                    .for_each(|(sum, value)| *sum += *value as u32);
+            };
+            line.chunks_exact(3)
+                .take(radius)
+                .for_each(|pixel| add(&mut sums, pixel));
+            let mut ahead = line.chunks_exact(3).skip(radius);
+            let mut behind = line.chunks_exact(3);
+            let pixels = line.chunks_exact(3).zip(glow.chunks_exact_mut(3));
+            for (x, (pixel, lit)) in pixels.enumerate() {
+                if let Some(pixel) = ahead.next() {
+                    add(&mut sums, pixel);
+                }
+                if x > radius {
+                    let pixel = behind.next().unwrap_or_default();
+                    sums.iter_mut()
+                        .zip(pixel)
+                        .for_each(|(sum, value)| *sum -= *value as u32);
+                }
+                // most of the court is black
+                if sums == [0; 3] {
+                    lit.fill(0);
+                    continue;
+                }
+                for ((lit, value), sum) in lit.iter_mut().zip(pixel).zip(sums) {
+                    *lit = (*value as u32 + sum * CRT_BLOOM / (256 * window)).min(255) as u8;
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test() {
+    let (width, height) = (64, 36);
+    let white = Image {
+        width,
+        height,
+        rgb: vec![255; (width * height * 3) as usize],
+    };
+    let pixel = |image: &Image, x: u32, y: u32| image.rgb[((y * width + x) * 3) as usize];
+
+    let mut crt = Crt::new();
+    let mut image = white.clone();
+    crt.apply(&mut image);
+    assert_eq!((image.width, image.height), (width, height));
+    assert_eq!(image.rgb.len(), white.rgb.len());
+    // the corners are off the curved screen, the centre keeps its light
+    assert_eq!(pixel(&image, 0, 0), 0);
+    assert_eq!(pixel(&image, width - 1, height - 1), 0);
+    assert_eq!(pixel(&image, width / 2, height / 2), 255);
+    // darker on a scanline and towards the sides
+    let lines: Vec<_> = (0..height).map(|y| pixel(&image, width / 2, y)).collect();
+    assert!(lines.iter().any(|value| *value < 255));
+    let mut image = Image {
+        rgb: vec![64; white.rgb.len()],
+        ..white.clone()
+    };
+    crt.apply(&mut image);
+    let (centre, side) = (pixel(&image, width / 2, 16), pixel(&image, 4, 16));
+    assert!(centre > side && side > 0);
+
+    // a lone bright pixel glows on its neighbours, until the frames are too slow
+    let mut dot = Image {
+        rgb: vec![0; white.rgb.len()],
+        ..white
+    };
+    let i = ((16 * width + width / 2) * 3) as usize;
+    dot.rgb[i..i + 3].fill(255);
+    let mut image = dot.clone();
+    crt.apply(&mut image);
+    assert!(pixel(&image, width / 2 + 1, 16) > 0);
+    for _ in 0..CRT_SLOW_FRAMES {
+        assert!(crt.has_bloom());
+        crt.record(Duration::from_millis(CRT_BUDGET_MS + 1));
+    }
+    assert!(!crt.has_bloom());
+    let mut image = dot;
+    crt.apply(&mut image);
+    assert_eq!(pixel(&image, width / 2 + 1, 16), 0);
+}
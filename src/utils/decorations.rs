@@ -0,0 +1,289 @@
+use crate::constants::*;
+use crate::error::InvalidPacket;
+use crate::packet::{PacketReader, PacketWriter};
+use crate::team::TeamName;
+use crate::Result;
+use sdl2::rect::Rect;
+use std::f32::consts::TAU;
+
+/// how a line of the court is drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineStyle {
+    None,
+    Solid,
+    Dashed,
+    Dotted,
+}
+
+impl LineStyle {
+    const ALL: [LineStyle; 4] = [
+        LineStyle::None,
+        LineStyle::Solid,
+        LineStyle::Dashed,
+        LineStyle::Dotted,
+    ];
+
+    fn parse(word: &str) -> Option<Self> {
+        match word {
+            "none" => Some(LineStyle::None),
+            "solid" => Some(LineStyle::Solid),
+            "dashed" => Some(LineStyle::Dashed),
+            "dotted" => Some(LineStyle::Dotted),
+            _ => None,
+        }
+    }
+
+    /// the start and the length of each piece of a line of `length`, the dashes
+    /// have the same size whatever the direction
+    fn pieces(self, length: u32) -> Vec<(i32, u32)> {
+        match self {
+            LineStyle::None => vec![],
+            LineStyle::Solid => vec![(0, length)],
+            LineStyle::Dashed => {
+                let halves = 2 * MID_LINE_SEGMENTS * length / WINDOW_HEIGHT;
+                (0..halves)
+                    .step_by(2)
+                    .map(|i| {
+                        let start = i * length / halves;
+                        (start as i32, (i + 1) * length / halves - start)
+                    })
+                    .collect()
+            }
+            LineStyle::Dotted => (0..length / (MID_LINE_WIDTH * 3))
+                .map(|i| ((i * MID_LINE_WIDTH * 3) as i32, MID_LINE_WIDTH))
+                .collect(),
+        }
+    }
+}
+
+/// one line of the decorations in a theme or a layout file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decoration {
+    CenterLine(LineStyle),
+    /// across the court, between the top and the bottom
+    HorizontalLine(LineStyle),
+    /// on the sides without a goal
+    Boundary(LineStyle),
+    /// the radius, in thousandths of the height of the court
+    Circle(u32),
+    /// the depth in front of each goal, in thousandths of the court
+    GoalZone(u32),
+}
+
+impl Decoration {
+    pub const KEYWORDS: [&'static str; 5] = [
+        "center-line",
+        "horizontal-line",
+        "boundary",
+        "circle",
+        "goal-zone",
+    ];
+
+    /// `name` is one of `KEYWORDS`, followed by a line style or a size
+    pub fn parse(name: &str, words: &[&str]) -> Option<Self> {
+        let style = || match words {
+            [word] => LineStyle::parse(word),
+            _ => None,
+        };
+        let size = || match words {
+            [word] => word.parse::<u32>().ok().filter(|size| *size <= 1000),
+            _ => None,
+        };
+        match name {
+            "center-line" => style().map(Decoration::CenterLine),
+            "horizontal-line" => style().map(Decoration::HorizontalLine),
+            "boundary" => style().map(Decoration::Boundary),
+            "circle" => size().map(Decoration::Circle),
+            "goal-zone" => size().map(Decoration::GoalZone),
+            _ => None,
+        }
+    }
+
+    pub fn write(&self, writer: &mut PacketWriter) {
+        let (kind, value) = match self {
+            Decoration::CenterLine(style) => (0, *style as u32),
+            Decoration::HorizontalLine(style) => (1, *style as u32),
+            Decoration::Boundary(style) => (2, *style as u32),
+            Decoration::Circle(radius) => (3, *radius),
+            Decoration::GoalZone(depth) => (4, *depth),
+        };
+        writer.put_u8(kind);
+        writer.put_u32(value);
+    }
+
+    pub fn read(reader: &mut PacketReader) -> Result<Self> {
+        let kind = reader.get_u8()?;
+        let value = reader.get_u32()?;
+        let style = || LineStyle::ALL.get(value as usize).copied();
+        let decoration = match kind {
+            0 => style().map(Decoration::CenterLine),
+            1 => style().map(Decoration::HorizontalLine),
+            2 => style().map(Decoration::Boundary),
+            3 => Some(Decoration::Circle(value)),
+            4 => Some(Decoration::GoalZone(value)),
+            _ => None,
+        };
+        Ok(decoration.ok_or(InvalidPacket)?)
+    }
+}
+
+/// what is drawn on the court below the game: the lines in the color of the mid
+/// line and the goal zones shaded; the classic court only has a dashed center line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Decorations {
+    pub center_line: LineStyle,
+    pub horizontal_line: LineStyle,
+    pub boundary: LineStyle,
+    pub circle: u32,
+    pub goal_zone: u32,
+}
+
+impl Default for Decorations {
+    fn default() -> Self {
+        Self {
+            center_line: LineStyle::Dashed,
+            horizontal_line: LineStyle::None,
+            boundary: LineStyle::None,
+            circle: 0,
+            goal_zone: 0,
+        }
+    }
+}
+
+impl Decorations {
+    pub fn set(&mut self, decoration: Decoration) {
+        match decoration {
+            Decoration::CenterLine(style) => self.center_line = style,
+            Decoration::HorizontalLine(style) => self.horizontal_line = style,
+            Decoration::Boundary(style) => self.boundary = style,
+            Decoration::Circle(radius) => self.circle = radius,
+            Decoration::GoalZone(depth) => self.goal_zone = depth,
+        }
+    }
+
+    /// with `decorations` over these, like the ones of an arena over a theme
+    pub fn with(mut self, decorations: &[Decoration]) -> Self {
+        for decoration in decorations {
+            self.set(*decoration);
+        }
+        self
+    }
+
+    /// the lines and the circle of a court with the goals of `teams`
+    pub fn lines(&self, teams: &[TeamName]) -> Vec<Rect> {
+        let (width, height) = (WINDOW_WIDTH, WINDOW_HEIGHT);
+        let thickness = MID_LINE_WIDTH;
+        let mut rects = vec![];
+        let x = (width - thickness) as i32 / 2;
+        for (y, length) in self.center_line.pieces(height) {
+            rects.push(Rect::new(x, y, thickness, length));
+        }
+        let y = (height - thickness) as i32 / 2;
+        for (x, length) in self.horizontal_line.pieces(width) {
+            rects.push(Rect::new(x, y, length, thickness));
+        }
+        for side in TeamName::ALL.iter().filter(|team| !teams.contains(team)) {
+            let length = if side.is_horizontal() { width } else { height };
+            for (start, length) in self.boundary.pieces(length) {
+                rects.push(match side {
+                    TeamName::Left => Rect::new(0, start, thickness, length),
+                    TeamName::Right => {
+                        Rect::new((width - thickness) as i32, start, thickness, length)
+                    }
+                    TeamName::Top => Rect::new(start, 0, length, thickness),
+                    TeamName::Bottom => {
+                        Rect::new(start, (height - thickness) as i32, length, thickness)
+                    }
+                });
+            }
+        }
+        if self.circle > 0 {
+            let radius = (self.circle * height / 1000) as f32;
+            let dots = (TAU * radius / thickness as f32).ceil() as u32;
+            for i in 0..dots {
+                let angle = TAU * i as f32 / dots as f32;
+                let x = width as f32 / 2.0 + radius * angle.cos();
+                let y = height as f32 / 2.0 + radius * angle.sin();
+                rects.push(Rect::from_center(
+                    (x as i32, y as i32),
+                    thickness,
+                    thickness,
+                ));
+            }
+        }
+        rects
+    }
+
+    /// shaded along the sides of `teams`
+    pub fn zones(&self, teams: &[TeamName]) -> Vec<Rect> {
+        if self.goal_zone == 0 {
+            return vec![];
+        }
+        let (width, height) = (WINDOW_WIDTH, WINDOW_HEIGHT);
+        let depth_x = (self.goal_zone * width / 1000).max(1);
+        let depth_y = (self.goal_zone * height / 1000).max(1);
+        teams
+            .iter()
+            .map(|team| match team {
+                TeamName::Left => Rect::new(0, 0, depth_x, height),
+                TeamName::Right => Rect::new((width - depth_x) as i32, 0, depth_x, height),
+                TeamName::Top => Rect::new(0, 0, width, depth_y),
+                TeamName::Bottom => Rect::new(0, (height - depth_y) as i32, width, depth_y),
+            })
+            .collect()
+    }
+}
+
+#[test]
+fn test() {
+    let teams = [TeamName::Left, TeamName::Right];
+    // the classic dashed line, a dash then a gap of the same height
+    let lines = Decorations::default().lines(&teams);
+    assert_eq!(lines.len(), MID_LINE_SEGMENTS as usize);
+    assert_eq!(lines[1].y(), 2 * lines[0].height() as i32);
+    assert!(Decorations::default().zones(&teams).is_empty());
+
+    let decorations = Decorations::default().with(&[
+        Decoration::parse("center-line", &["solid"]).unwrap(),
+        Decoration::parse("boundary", &["dotted"]).unwrap(),
+        Decoration::parse("goal-zone", &["100"]).unwrap(),
+    ]);
+    let lines = decorations.lines(&teams);
+    let x = (WINDOW_WIDTH - MID_LINE_WIDTH) as i32 / 2;
+    assert_eq!(lines[0], Rect::new(x, 0, MID_LINE_WIDTH, WINDOW_HEIGHT));
+    // the boundary is only on the top and the bottom sides
+    assert!(lines[1..]
+        .iter()
+        .all(|rect| rect.y() == 0 || rect.bottom() == WINDOW_HEIGHT as i32));
+    let zones = decorations.zones(&teams);
+    assert_eq!(
+        zones[1],
+        Rect::new(
+            (WINDOW_WIDTH * 9 / 10) as i32,
+            0,
+            WINDOW_WIDTH / 10,
+            WINDOW_HEIGHT
+        )
+    );
+
+    let circle = Decorations::default().with(&[
+        Decoration::Circle(250),
+        Decoration::CenterLine(LineStyle::None),
+    ]);
+    let centre = ((WINDOW_WIDTH / 2) as i32, (WINDOW_HEIGHT / 2) as i32);
+    for rect in circle.lines(&teams) {
+        let (dx, dy) = (rect.center().x() - centre.0, rect.center().y() - centre.1);
+        let distance = ((dx * dx + dy * dy) as f32).sqrt();
+        assert!((distance - WINDOW_HEIGHT as f32 / 4.0).abs() < 2.0);
+    }
+
+    assert!(Decoration::parse("circle", &["1001"]).is_none());
+    assert!(Decoration::parse("boundary", &["wavy"]).is_none());
+    let mut writer = PacketWriter::new(0);
+    Decoration::Boundary(LineStyle::Dotted).write(&mut writer);
+    let mut reader = PacketReader::new(&writer.bytes()[1..]);
+    assert_eq!(
+        Decoration::read(&mut reader).unwrap(),
+        Decoration::Boundary(LineStyle::Dotted)
+    );
+}
@@ -18,3 +18,57 @@ impl fmt::Display for IndexTooHigh {
     }
 }
 impl error::Error for IndexTooHigh {}
+
+#[derive(Debug, Clone)]
+pub struct PacketTooShort;
+impl fmt::Display for PacketTooShort {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Packet too short")
+    }
+}
+impl error::Error for PacketTooShort {}
+
+#[derive(Debug, Clone)]
+pub struct ConnectionTimeout;
+impl fmt::Display for ConnectionTimeout {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Connection timed out")
+    }
+}
+impl error::Error for ConnectionTimeout {}
+
+#[derive(Debug, Clone)]
+pub struct InvalidArgument(pub String);
+impl fmt::Display for InvalidArgument {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid argument: {}", self.0)
+    }
+}
+impl error::Error for InvalidArgument {}
+
+#[derive(Debug, Clone)]
+pub struct InvalidPacket;
+impl fmt::Display for InvalidPacket {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid packet")
+    }
+}
+impl error::Error for InvalidPacket {}
+
+#[derive(Debug, Clone)]
+pub struct InvalidLayout(pub String);
+impl fmt::Display for InvalidLayout {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid arena layout: {}", self.0)
+    }
+}
+impl error::Error for InvalidLayout {}
+
+#[derive(Debug, Clone)]
+pub struct InvalidTheme(pub String);
+impl fmt::Display for InvalidTheme {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid theme: {}", self.0)
+    }
+}
+impl error::Error for InvalidTheme {}
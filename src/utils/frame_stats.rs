@@ -0,0 +1,50 @@
+use std::time::Duration;
+
+/// frames per second and the time spent on a frame, averaged over about a second
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct FrameStats {
+    frames: u32,
+    /// time between the start of the frames counted so far
+    elapsed: Duration,
+    /// time spent updating and drawing them
+    busy: Duration,
+    fps: u32,
+    frame_time: Duration,
+}
+
+impl FrameStats {
+    /// `interval` is the time since the start of the previous frame, `busy` the time
+    /// spent on this one without sleeping
+    pub fn record(&mut self, interval: Duration, busy: Duration) {
+        self.frames += 1;
+        self.elapsed += interval;
+        self.busy += busy;
+        if self.elapsed >= Duration::from_secs(1) {
+            self.fps = (self.frames as f64 / self.elapsed.as_secs_f64()).round() as u32;
+            self.frame_time = self.busy / self.frames;
+            self.frames = 0;
+            self.elapsed = Duration::ZERO;
+            self.busy = Duration::ZERO;
+        }
+    }
+
+    pub fn fps(&self) -> u32 {
+        self.fps
+    }
+
+    pub fn frame_time(&self) -> Duration {
+        self.frame_time
+    }
+}
+
+#[test]
+fn test() {
+    let mut stats = FrameStats::default();
+    for _ in 0..49 {
+        stats.record(Duration::from_millis(20), Duration::from_millis(4));
+    }
+    assert_eq!(stats.fps(), 0);
+    stats.record(Duration::from_millis(20), Duration::from_millis(4));
+    assert_eq!(stats.fps(), 50);
+    assert_eq!(stats.frame_time(), Duration::from_millis(4));
+}
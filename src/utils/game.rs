@@ -1,75 +1,226 @@
-use sdl2::event::Event;
+use sdl2::event::{Event, WindowEvent};
 use sdl2::keyboard::Keycode;
 use sdl2::pixels::Color;
-use sdl2::render::Canvas;
-use sdl2::video::Window;
+use sdl2::rect::{Point, Rect};
+use sdl2::render::{BlendMode, Canvas};
+use sdl2::video::{FullscreenType, Window};
 use sdl2::EventPump;
 use std::thread;
 use std::time::{Duration, SystemTime};
 
-use crate::ball::Ball;
+use crate::ai::Ai;
+use crate::arena::Arena;
+use crate::bricks::Bricks;
+use crate::capture::Capture;
 use crate::constants::*;
-use crate::error::GameInfiniteLoop;
+use crate::crt::Crt;
+use crate::error::{GameInfiniteLoop, InvalidArgument};
+use crate::frame_stats::FrameStats;
 use crate::game_status::GameStatus;
-use crate::mid_line::DashedLineVert;
-use crate::paddle::Paddle;
+use crate::juice::Juice;
+use crate::mode::Mode;
+use crate::net::NetInput;
+use crate::netcode::Netcode;
+use crate::paddle::PaddleMove;
+use crate::personal_best::PersonalBest;
 use crate::point_display::PointDisplay;
+use crate::powerup::PowerUpKind;
+use crate::rules::Rules;
+use crate::sound::Sound;
+use crate::spectator::{Snapshot, SpectatorServer};
+use crate::team::TeamName;
+use crate::theme::Theme;
+use crate::world::{MultiBall, RallySpeed, World};
 use crate::Result;
 
+/// who moves a paddle while playing
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Control {
+    Keys(Keycode, Keycode),
+    Cpu,
+}
+
 pub struct Game<'a> {
     canvas: Canvas<Window>,
     point_display: PointDisplay<'a>,
     events: EventPump,
-    mid_line: DashedLineVert<'a>,
-    paddle_l: Paddle,
-    paddle_r: Paddle,
-    ball: Ball,
+    world: World,
+    ais: Vec<Ai>,
+    controls: Vec<Control>,
     fps: u64,
     status: GameStatus,
     prev_status: GameStatus,
+    last_input: SystemTime,
+    net: Option<Netcode>,
+    spectators: Option<SpectatorServer>,
+    /// shown by the right counter in squash
+    personal_best: Option<PersonalBest>,
+    /// draws the physics on top of the court, toggled with `DEBUG`
+    debug: bool,
+    frame_stats: FrameStats,
+    capture: Capture,
+    theme: Theme,
+    juice: Juice,
+    crt: Option<Crt>,
+    /// pauses when the window loses the focus or is minimized
+    auto_pause: bool,
+    /// in ticks, the balls wait after a pause
+    resume_countdown: u32,
+    sound: Sound,
 }
 
 impl<'a> Game<'a> {
     pub fn new(
         canvas: Canvas<Window>,
-        point_display: PointDisplay<'a>,
+        mut point_display: PointDisplay<'a>,
         events: EventPump,
-        mid_line: DashedLineVert<'a>,
         fps: u64,
+        net: Option<Netcode>,
+        mode: Mode,
     ) -> Self {
+        let seed = match &net {
+            Some(netcode) => netcode.session().seed(),
+            None => rand::random(),
+        };
+        let status = match net {
+            Some(_) => GameStatus::Play,
+            None => GameStatus::Neutral,
+        };
+        let world = World::new(mode, seed);
+        let ais = Ai::for_paddles(&world);
+        point_display.set_animate(true);
+        let mut controls = vec![];
+        for (i, paddle) in world.paddles().iter().enumerate() {
+            let row = world.paddles()[..i]
+                .iter()
+                .filter(|other| other.team() == paddle.team())
+                .count();
+            let (up, down) = paddle_keys(paddle.team(), row);
+            controls.push(Control::Keys(up, down));
+        }
         Self {
             canvas,
             point_display,
             events,
-            mid_line,
-            paddle_l: Paddle::new(
-                PADDLE_L_X,
-                (WINDOW_HEIGHT - PADDLE_L_HEIGHT) as i32 / 2,
-                PADDLE_WIDTH,
-                PADDLE_L_HEIGHT,
-                PADDLE_L_STEP,
-            ),
-            paddle_r: Paddle::new(
-                PADDLE_R_X,
-                (WINDOW_HEIGHT - PADDLE_R_HEIGHT) as i32 / 2,
-                PADDLE_WIDTH,
-                PADDLE_R_HEIGHT,
-                PADDLE_R_STEP,
-            ),
-            ball: Ball::new_rng(
-                (WINDOW_WIDTH - BALL_DIAMETER) as i32 / 2,
-                (WINDOW_HEIGHT - BALL_DIAMETER) as i32 / 2,
-                BALL_DIAMETER,
-                BALL_VX,
-                BALL_VY,
-                MULTIPLIER,
-                SLOW_START,
-            ),
+            world,
+            ais,
+            controls,
             fps,
-            status: GameStatus::Neutral,
+            status,
             prev_status: GameStatus::Play,
+            last_input: SystemTime::now(),
+            net,
+            spectators: None,
+            personal_best: None,
+            debug: false,
+            frame_stats: FrameStats::default(),
+            capture: Capture::default(),
+            theme: Theme::default(),
+            juice: Juice::new(seed),
+            crt: None,
+            auto_pause: true,
+            resume_countdown: 0,
+            sound: Sound::default(),
+        }
+    }
+
+    pub fn set_personal_best(&mut self, personal_best: PersonalBest) {
+        self.personal_best = Some(personal_best);
+    }
+
+    pub fn set_debug(&mut self, debug: bool) {
+        self.debug = debug;
+        self.point_display.show_stats(debug);
+    }
+
+    /// the counters are drawn with the theme given when they are made
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    /// without the sparks, the trail, the shake, the flash and the growing counters
+    pub fn set_reduced_motion(&mut self, reduced_motion: bool) {
+        self.juice.set_enabled(!reduced_motion);
+        self.point_display.set_animate(!reduced_motion);
+    }
+
+    pub fn set_crt(&mut self, crt: bool) {
+        self.crt = crt.then(Crt::new);
+    }
+
+    pub fn set_auto_pause(&mut self, auto_pause: bool) {
+        self.auto_pause = auto_pause;
+    }
+
+    /// in ticks, the balls stay frozen that long when resumed, the paddles move
+    pub fn set_resume_countdown(&mut self, ticks: u32) {
+        self.resume_countdown = ticks;
+    }
+
+    /// in ticks, the balls wait that long before each serve
+    pub fn set_countdown(&mut self, ticks: u32) {
+        self.world.set_countdown(ticks);
+    }
+
+    pub fn set_sound(&mut self, sound: Sound) {
+        self.sound = sound;
+    }
+
+    pub fn set_capture(&mut self, capture: Capture) {
+        self.capture = capture;
+    }
+
+    pub fn set_spectators(&mut self, server: SpectatorServer) {
+        self.spectators = Some(server);
+    }
+
+    pub fn set_multi_ball(&mut self, multi_ball: MultiBall) {
+        self.world.set_multi_ball(multi_ball);
+    }
+
+    pub fn set_rally_speed(&mut self, rally_speed: RallySpeed) {
+        self.world.set_rally_speed(rally_speed);
+    }
+
+    pub fn set_power_ups(&mut self, enabled: &[PowerUpKind]) {
+        self.world.set_power_ups(enabled);
+    }
+
+    pub fn set_rules(&mut self, rules: Rules) {
+        self.world.set_rules(rules);
+        self.point_display.show_sets(rules.sets > 1);
+        self.point_display.show_clock(rules.time_limit.is_some());
+    }
+
+    /// in ticks, `None` serves the ball from the centre
+    pub fn set_serve_timeout(&mut self, timeout: Option<u32>) {
+        self.world.set_serve_timeout(timeout);
+    }
+
+    pub fn set_spin(&mut self, spin: bool) {
+        self.world.set_spin(spin);
+    }
+
+    pub fn set_arena(&mut self, arena: Arena) {
+        self.world.set_arena(arena);
+    }
+
+    pub fn set_bricks(&mut self, bricks: Bricks) {
+        self.world.set_bricks(bricks);
+    }
+
+    /// `paddles` are numbered from 1, in the order of `World::paddles`
+    pub fn set_cpu(&mut self, paddles: &[usize]) -> Result<()> {
+        for paddle in paddles {
+            let control = paddle
+                .checked_sub(1)
+                .and_then(|i| self.controls.get_mut(i))
+                .ok_or_else(|| InvalidArgument(format!("--cpu {paddle}")))?;
+            *control = Control::Cpu;
         }
+        Ok(())
     }
+
     pub fn spawn(&mut self) -> Result<()> {
         self.draw()?;
         let mut loop_start_time = SystemTime::now();
@@ -81,10 +232,19 @@ impl<'a> Game<'a> {
             if frame_duration > elapsed_time {
                 thread::sleep(Duration::from_nanos(1_000_000_000u64 / self.fps) - elapsed_time);
             }
-            loop_start_time = SystemTime::now();
+            let now = SystemTime::now();
+            self.frame_stats
+                .record(now.duration_since(loop_start_time)?, elapsed_time);
+            loop_start_time = now;
+            if self.debug {
+                let frame_time = self.frame_stats.frame_time().as_millis() as u32;
+                self.point_display
+                    .set_stats(self.frame_stats.fps(), frame_time)?;
+            }
             self.update_status()?;
             if draw {
                 match self.status {
+                    GameStatus::Play if self.net.is_some() => self.play_online()?,
                     GameStatus::Play => self.play()?,
                     GameStatus::Reset => self.reset()?,
                     GameStatus::Neutral => self.neutral()?,
@@ -92,130 +252,389 @@ impl<'a> Game<'a> {
                     GameStatus::Quit => break,
                 }
             }
+            if let Some(server) = &mut self.spectators {
+                server.broadcast(&Snapshot::new(&self.world, self.status))?;
+            }
             match self.status {
                 GameStatus::Waiting => draw = false,
                 GameStatus::Quit => break,
+                _ if self.capture.is_done() => break,
                 _ => draw = true,
             }
         }
+        self.save_personal_best()?;
+        if self.capture.stop()? {
+            self.draw()?;
+        }
+        if let Some(netcode) = &mut self.net {
+            netcode.session_mut().disconnect()?;
+        }
         Ok(())
     }
 
     fn play(&mut self) -> Result<()> {
-        for key_pressed in self
-            .events
-            .keyboard_state()
-            .pressed_scancodes()
-            .filter_map(Keycode::from_scancode)
-        {
-            match key_pressed {
-                PADDLE_L_UP => self.paddle_l.move_up(0, &mut self.ball),
-                PADDLE_L_DOWN => self
-                    .paddle_l
-                    .move_down(WINDOW_HEIGHT as i32, &mut self.ball),
-                PADDLE_R_UP => self.paddle_r.move_up(0, &mut self.ball),
-                PADDLE_R_DOWN => self
-                    .paddle_r
-                    .move_down(WINDOW_HEIGHT as i32, &mut self.ball),
-                _ => {}
+        let keys = self.pressed_keys();
+        let mut moves = vec![];
+        for (i, control) in self.controls.iter().enumerate() {
+            let movement = match control {
+                Control::Keys(up, down) => key_move(&keys, *up, *down),
+                Control::Cpu => self.cpu_move(i),
+            };
+            if matches!(control, Control::Keys(..)) && movement != PaddleMove::Stay {
+                self.last_input = SystemTime::now();
             }
+            moves.push(movement);
         }
+        self.serve(&keys);
 
-        let points = self.ball.change_position(
-            Some((&self.paddle_l, &self.paddle_r)),
-            0,
-            WINDOW_WIDTH as i32,
-            0,
-            WINDOW_HEIGHT as i32,
-        );
-
-        if let Some(team) = points? {
-            self.point_display.incr_point(team)?;
-            self.ball.after_goal_rng(
-                (WINDOW_WIDTH - BALL_DIAMETER) as i32 / 2,
-                (WINDOW_HEIGHT - BALL_DIAMETER) as i32 / 2,
-                team,
-            );
+        let conceded = self.world.step(&moves)?;
+        self.juice.update(&self.world);
+        self.update_countdown()?;
+        if let Some(personal_best) = &mut self.personal_best {
+            // the streaks of the cpu are no record
+            if !self.controls.contains(&Control::Cpu) {
+                personal_best.update(self.world.points(TeamName::Left));
+            }
         }
+        if !conceded.is_empty() {
+            self.save_personal_best()?;
+        }
+        self.update_points()?;
 
-        if self.point_display.left() == POINT_TO_WIN || self.point_display.right() == POINT_TO_WIN {
+        if self.world.winner().is_some() {
+            self.change_status(GameStatus::Neutral);
+        } else if SystemTime::now().duration_since(self.last_input)?
+            > Duration::from_secs(IDLE_TIMEOUT)
+        {
+            self.save_personal_best()?;
+            self.world.reset_points();
+            self.update_points()?;
             self.change_status(GameStatus::Neutral);
         }
         self.draw()?;
         Ok(())
     }
 
+    /// the local player moves only its own paddle, the other one is driven by the peer
+    fn play_online(&mut self) -> Result<()> {
+        let keys = self.pressed_keys();
+        let netcode = self.net.as_mut().expect("It can't be None");
+        let (up, down) = paddle_keys(netcode.session().side(), 0);
+        let movement = key_move(&keys, up, down);
+        let local = NetInput {
+            movement,
+            reset: false,
+        };
+        if !netcode.update(&mut self.world, local)? {
+            self.net = None;
+            self.change_status(GameStatus::Neutral);
+        }
+        self.juice.update(&self.world);
+        self.update_countdown()?;
+        self.update_points()?;
+        if self.world.winner().is_some() {
+            self.draw_pause()?;
+        } else {
+            self.draw()?;
+        }
+        Ok(())
+    }
+
+    /// the cpu serves right away
+    fn serve(&mut self, keys: &[Keycode]) {
+        for team in self.world.mode().teams() {
+            let Some(i) = self
+                .world
+                .paddles()
+                .iter()
+                .position(|paddle| paddle.team() == *team)
+            else {
+                continue;
+            };
+            let pressed = match (self.controls[i], serve_key(*team)) {
+                (Control::Cpu, _) => true,
+                (Control::Keys(..), Some(key)) => keys.contains(&key),
+                (Control::Keys(..), None) => false,
+            };
+            if pressed && self.world.is_serving(*team) {
+                self.world.serve(*team);
+            }
+        }
+    }
+
+    fn update_points(&mut self) -> Result<()> {
+        self.point_display.tick();
+        for team in self.world.mode().teams() {
+            self.point_display
+                .set_point(*team, self.world.points(*team))?;
+            self.point_display.set_sets(*team, self.world.sets(*team))?;
+        }
+        if let Some(personal_best) = &self.personal_best {
+            self.point_display
+                .set_point(TeamName::Right, personal_best.best())?;
+        }
+        if let Some(ticks) = self.world.time_left() {
+            self.point_display.set_clock(ticks.div_ceil(FPS as u32))?;
+        }
+        Ok(())
+    }
+
+    /// the seconds left before the balls move, a beep for each of them and a
+    /// higher one when it is over
+    fn update_countdown(&mut self) -> Result<()> {
+        let seconds = self.world.countdown_left().div_ceil(FPS as u32);
+        if seconds != self.point_display.countdown() {
+            self.sound.beep(match seconds {
+                0 => COUNTDOWN_GO_BEEP,
+                _ => COUNTDOWN_BEEP,
+            });
+            self.point_display.set_countdown(seconds)?;
+        }
+        Ok(())
+    }
+
+    fn pressed_keys(&self) -> Vec<Keycode> {
+        self.events
+            .keyboard_state()
+            .pressed_scancodes()
+            .filter_map(Keycode::from_scancode)
+            .collect()
+    }
+
     fn change_status(&mut self, status: GameStatus) {
         self.prev_status = self.status;
         self.status = status;
     }
 
     fn draw(&mut self) -> Result<()> {
-        self.draw_color(PADDLE_COLOR, BALL_COLOR, BACKGROUND_COLOR)?;
+        self.draw_color(false)?;
         Ok(())
     }
 
     fn draw_pause(&mut self) -> Result<()> {
-        self.draw_color(PADDLE_COLOR_PAUSE, BALL_COLOR_PAUSE, BACKGROUND_COLOR)?;
+        self.draw_color(true)?;
         Ok(())
     }
 
-    fn draw_color(
-        &mut self,
-        paddle_color: Color,
-        ball_color: Color,
-        background_color: Color,
-    ) -> Result<()> {
+    /// the paddles and the balls are dimmed while paused, and the effects are
+    /// only drawn while playing
+    fn draw_color(&mut self, paused: bool) -> Result<()> {
         self.canvas.clear();
+        let viewport = self.canvas.viewport();
+        if !paused {
+            let (x, y) = self.juice.offset();
+            let mut shaken = viewport;
+            shaken.offset(x, y);
+            self.canvas.set_viewport(shaken);
+        }
+        self.draw_decorations()?;
         self.point_display.draw(&mut self.canvas)?;
-        self.mid_line.draw(&mut self.canvas)?;
-        self.canvas.set_draw_color(paddle_color);
-        self.canvas.fill_rect(self.paddle_l.rect())?;
-        self.canvas.fill_rect(self.paddle_r.rect())?;
-        self.canvas.set_draw_color(ball_color);
-        self.canvas.fill_rect(self.ball.rect())?;
-        self.canvas.set_draw_color(background_color);
+        self.draw_arena()?;
+        self.draw_power_ups()?;
+        for paddle in self.world.active_paddles() {
+            self.canvas
+                .set_draw_color(self.theme.paddle(paddle.team(), paused));
+            self.canvas.fill_rect(paddle.rect())?;
+        }
+        if !paused {
+            self.draw_trail()?;
+        }
+        self.canvas.set_draw_color(self.theme.ball(paused));
+        for ball in self.world.balls() {
+            self.canvas.fill_rect(ball.rect())?;
+        }
+        if !paused {
+            self.draw_effects()?;
+        }
+        if self.debug {
+            self.draw_debug()?;
+        }
+        self.canvas.set_viewport(viewport);
+        if let Some(crt) = &mut self.crt {
+            crt.draw(&mut self.canvas)?;
+        }
+        self.capture.frame(&mut self.canvas)?;
+        self.canvas.set_draw_color(self.theme.background);
         self.canvas.present();
         Ok(())
     }
 
+    /// the ones of the theme with the ones of the arena over them
+    fn draw_decorations(&mut self) -> Result<()> {
+        let decorations = self
+            .theme
+            .decorations
+            .with(self.world.arena().decorations());
+        let goals = self.world.court().goals;
+        let teams: Vec<_> = goals.iter().map(|(team, ..)| *team).collect();
+        self.canvas.set_draw_color(self.theme.zone);
+        self.canvas.fill_rects(&decorations.zones(&teams))?;
+        self.canvas.set_draw_color(self.theme.mid_line);
+        self.canvas.fill_rects(&decorations.lines(&teams))?;
+        Ok(())
+    }
+
+    fn draw_trail(&mut self) -> Result<()> {
+        let (r, g, b) = self.theme.ball.rgb();
+        self.canvas.set_blend_mode(BlendMode::Blend);
+        for (rect, alpha) in self.juice.trail() {
+            self.canvas.set_draw_color(Color::RGBA(r, g, b, alpha));
+            self.canvas.fill_rect(rect)?;
+        }
+        self.canvas.set_blend_mode(BlendMode::None);
+        Ok(())
+    }
+
+    /// the sparks, then the flash over the whole court
+    fn draw_effects(&mut self) -> Result<()> {
+        let (r, g, b) = self.theme.ball.rgb();
+        self.canvas.set_blend_mode(BlendMode::Blend);
+        for (rect, alpha) in self.juice.sparks() {
+            self.canvas.set_draw_color(Color::RGBA(r, g, b, alpha));
+            self.canvas.fill_rect(rect)?;
+        }
+        if self.juice.flash() > 0 {
+            let (r, g, b) = self.theme.mid_line.rgb();
+            self.canvas
+                .set_draw_color(Color::RGBA(r, g, b, self.juice.flash()));
+            self.canvas.fill_rect(None)?;
+        }
+        self.canvas.set_blend_mode(BlendMode::None);
+        Ok(())
+    }
+
+    fn draw_arena(&mut self) -> Result<()> {
+        let arena = self.world.arena();
+        self.canvas.set_draw_color(self.theme.obstacle);
+        self.canvas
+            .fill_rects(&arena.obstacles(self.world.tick()))?;
+        self.canvas
+            .fill_rects(&arena.posts(self.world.mode().teams()))?;
+        for wall in arena.walls() {
+            self.canvas.draw_line(wall.start(), wall.end())?;
+        }
+        // the color tells how many hits a brick still takes
+        for (_, rect, hits) in self.world.bricks().alive() {
+            self.canvas
+                .set_draw_color(BRICK_COLORS[hits.clamp(1, 3) as usize - 1]);
+            self.canvas.fill_rect(rect)?;
+        }
+        Ok(())
+    }
+
+    /// for each ball the walls checked in its direction, its velocity and its path,
+    /// then the middle of the paddles, where the bounce is straight
+    fn draw_debug(&mut self) -> Result<()> {
+        let paddles: Vec<_> = self.world.active_paddles().collect();
+        let court = self.world.court();
+        for ball in self.world.balls() {
+            self.canvas.set_draw_color(DEBUG_COLOR);
+            for wall in ball.walls(&paddles, &court) {
+                self.canvas.draw_line(wall.start(), wall.end())?;
+            }
+            let (x, y) = ball.center();
+            let (vx, vy) = (ball.vx(), ball.vy());
+            self.canvas.draw_line(
+                (x, y),
+                (x + vx * DEBUG_VELOCITY_SCALE, y + vy * DEBUG_VELOCITY_SCALE),
+            )?;
+            let path: Vec<_> = ball
+                .predict(&paddles, &court, DEBUG_PREDICTION)
+                .into_iter()
+                .map(Point::from)
+                .collect();
+            self.canvas.set_draw_color(DEBUG_PATH_COLOR);
+            self.canvas.draw_lines(path.as_slice())?;
+        }
+        self.canvas.set_draw_color(DEBUG_COLOR);
+        for paddle in &paddles {
+            let rect = paddle.rect();
+            let (mid_x, mid_y) = (
+                rect.x() + rect.width() as i32 / 2,
+                rect.y() + rect.height() as i32 / 2,
+            );
+            if paddle.is_horizontal() {
+                self.canvas
+                    .draw_line((mid_x, rect.y()), (mid_x, rect.bottom()))?;
+            } else {
+                self.canvas
+                    .draw_line((rect.x(), mid_y), (rect.right(), mid_y))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// pickups in the court, and one bar for each effect on the side of the team
+    /// that collected it, getting shorter as the effect runs out
+    fn draw_power_ups(&mut self) -> Result<()> {
+        let power_ups = self.world.power_ups();
+        for pickup in power_ups.pickups() {
+            self.canvas.set_draw_color(pickup.kind.color());
+            self.canvas.fill_rect(pickup.rect)?;
+        }
+        let mut shown = [0; 4];
+        for effect in power_ups.effects() {
+            let team = self.world.paddles()[effect.paddle].team();
+            let width = (2 * PICKUP_SIZE * effect.ticks_left / effect.kind.duration()).max(1);
+            let offset = (shown[team.index()] * (2 * PICKUP_SIZE + PICKUP_SIZE / 2)
+                + PICKUP_SIZE / 2) as i32;
+            shown[team.index()] += 1;
+            let x = match team {
+                TeamName::Left | TeamName::Top => offset,
+                TeamName::Right | TeamName::Bottom => WINDOW_WIDTH as i32 - offset - width as i32,
+            };
+            let y = (WINDOW_HEIGHT - PICKUP_SIZE) as i32;
+            self.canvas.set_draw_color(effect.kind.color());
+            self.canvas
+                .fill_rect(Rect::new(x, y, width, PICKUP_SIZE / 2))?;
+        }
+        Ok(())
+    }
+
+    /// attract mode: the cpu plays against itself until a key is pressed
     fn neutral(&mut self) -> Result<()> {
-        self.ball
-            .change_position(None, 0, WINDOW_WIDTH as i32, 0, WINDOW_HEIGHT as i32)?;
+        let moves: Vec<_> = (0..self.world.paddles().len())
+            .map(|i| self.cpu_move(i))
+            .collect();
+        for team in self.world.mode().teams() {
+            self.world.serve(*team);
+        }
+
+        // the cpu playing alone is not counted down
+        self.world.skip_countdown();
+        let game_over = self.world.winner().is_some();
+        if !self.world.step(&moves)?.is_empty() && game_over {
+            self.world.reset_points();
+        }
+        self.point_display.set_countdown(0)?;
+        self.update_points()?;
         self.draw_pause()?;
         Ok(())
     }
 
+    fn cpu_move(&self, i: usize) -> PaddleMove {
+        self.ais[i].paddle_move(&self.world, i)
+    }
+
     fn waiting(&mut self) -> Result<()> {
         self.draw_pause()?;
         Ok(())
     }
 
+    /// the record is written once a streak is over
+    fn save_personal_best(&mut self) -> Result<()> {
+        if let Some(personal_best) = &mut self.personal_best {
+            personal_best.save()?;
+        }
+        Ok(())
+    }
+
     fn reset(&mut self) -> Result<()> {
-        self.point_display.reset()?;
-        self.paddle_l = Paddle::new(
-            PADDLE_L_X,
-            (WINDOW_HEIGHT - PADDLE_L_HEIGHT) as i32 / 2,
-            PADDLE_WIDTH,
-            PADDLE_L_HEIGHT,
-            PADDLE_L_STEP,
-        );
-        self.paddle_r = Paddle::new(
-            PADDLE_R_X,
-            (WINDOW_HEIGHT - PADDLE_R_HEIGHT) as i32 / 2,
-            PADDLE_WIDTH,
-            PADDLE_R_HEIGHT,
-            PADDLE_R_STEP,
-        );
-        self.ball = Ball::new_rng(
-            (WINDOW_WIDTH - BALL_DIAMETER) as i32 / 2,
-            (WINDOW_HEIGHT - BALL_DIAMETER) as i32 / 2,
-            BALL_DIAMETER,
-            BALL_VX,
-            BALL_VY,
-            MULTIPLIER,
-            SLOW_START,
-        );
+        self.save_personal_best()?;
+        self.world.reset();
+        self.juice.clear();
+        self.point_display.set_countdown(0)?;
+        self.update_points()?;
         self.change_status(GameStatus::Play);
         Ok(())
     }
@@ -227,14 +646,61 @@ impl<'a> Game<'a> {
         Ok(())
     }
 
+    /// while paused it waits for the events instead of polling them every frame
     fn check_events(&mut self) -> Result<Option<GameStatus>> {
-        for event in self.events.poll_iter() {
+        let waited = match self.status {
+            GameStatus::Waiting => self.events.wait_event_timeout(PAUSE_WAIT),
+            _ => None,
+        };
+        let mut exposed = false;
+        for event in waited.into_iter().chain(self.events.poll_iter()) {
+            if let Event::KeyDown { .. } = event {
+                self.last_input = SystemTime::now();
+            }
             match event {
                 Event::Quit { .. }
                 | Event::KeyDown {
                     keycode: Some(QUIT),
                     ..
                 } => return Ok(Some(GameStatus::Quit)),
+                Event::KeyDown {
+                    keycode: Some(DEBUG),
+                    ..
+                } => {
+                    self.debug = !self.debug;
+                    self.point_display.show_stats(self.debug);
+                }
+                Event::KeyDown {
+                    keycode: Some(SCREENSHOT),
+                    ..
+                } => self.capture.screenshot(),
+                Event::KeyDown {
+                    keycode: Some(FULLSCREEN),
+                    ..
+                } => toggle_fullscreen(&mut self.canvas)?,
+                Event::Window {
+                    win_event: WindowEvent::FocusLost | WindowEvent::Minimized,
+                    ..
+                } if self.auto_pause && self.status == GameStatus::Play && self.net.is_none() => {
+                    return Ok(Some(GameStatus::Waiting))
+                }
+                Event::Window {
+                    win_event: WindowEvent::Exposed,
+                    ..
+                } => exposed = true,
+                Event::KeyDown { .. } if self.status == GameStatus::Neutral => {
+                    return Ok(Some(GameStatus::Reset))
+                }
+                Event::KeyDown {
+                    keycode: Some(RESET),
+                    ..
+                } if self.net.is_some() => {
+                    self.net.as_mut().expect("It can't be None").request_reset();
+                }
+                Event::KeyDown {
+                    keycode: Some(PAUSE),
+                    ..
+                } if self.net.is_some() => {}
                 Event::KeyDown {
                     keycode: Some(RESET),
                     ..
@@ -247,6 +713,9 @@ impl<'a> Game<'a> {
                         if self.prev_status == GameStatus::Waiting {
                             return Err(Box::new(GameInfiniteLoop));
                         }
+                        if self.prev_status == GameStatus::Play {
+                            self.world.freeze(self.resume_countdown);
+                        }
                         return Ok(Some(self.prev_status));
                     }
                     return Ok(Some(GameStatus::Waiting));
@@ -254,6 +723,50 @@ impl<'a> Game<'a> {
                 _ => {}
             }
         }
+        // a window shown again after being hidden has lost its content
+        if exposed && self.status == GameStatus::Waiting {
+            self.draw_pause()?;
+        }
         Ok(None)
     }
 }
+
+/// the court keeps its logical size, scaled to the screen
+pub fn toggle_fullscreen(canvas: &mut Canvas<Window>) -> Result<()> {
+    let window = canvas.window_mut();
+    let fullscreen = match window.fullscreen_state() {
+        FullscreenType::Off => FullscreenType::Desktop,
+        _ => FullscreenType::Off,
+    };
+    window.set_fullscreen(fullscreen)?;
+    Ok(())
+}
+
+/// `row` is 0 for the back paddle of a team and 1 for the forward one
+pub fn paddle_keys(team: TeamName, row: usize) -> (Keycode, Keycode) {
+    match (team, row) {
+        (TeamName::Left, 0) => (PADDLE_L_UP, PADDLE_L_DOWN),
+        (TeamName::Left, _) => (PADDLE_LF_UP, PADDLE_LF_DOWN),
+        (TeamName::Right, 0) => (PADDLE_R_UP, PADDLE_R_DOWN),
+        (TeamName::Right, _) => (PADDLE_RF_UP, PADDLE_RF_DOWN),
+        (TeamName::Top, _) => (PADDLE_T_LEFT, PADDLE_T_RIGHT),
+        (TeamName::Bottom, _) => (PADDLE_B_LEFT, PADDLE_B_RIGHT),
+    }
+}
+
+/// only the teams of a two teams match serve
+pub fn serve_key(team: TeamName) -> Option<Keycode> {
+    match team {
+        TeamName::Left => Some(SERVE_L),
+        TeamName::Right => Some(SERVE_R),
+        TeamName::Top | TeamName::Bottom => None,
+    }
+}
+
+pub fn key_move(keys: &[Keycode], up: Keycode, down: Keycode) -> PaddleMove {
+    match (keys.contains(&up), keys.contains(&down)) {
+        (true, false) => PaddleMove::Up,
+        (false, true) => PaddleMove::Down,
+        _ => PaddleMove::Stay,
+    }
+}
@@ -0,0 +1,196 @@
+use crate::constants::*;
+use crate::world::{Hit, World};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use sdl2::rect::Rect;
+use std::collections::VecDeque;
+
+#[derive(Debug, Clone, PartialEq)]
+struct Spark {
+    x: f32,
+    y: f32,
+    vx: f32,
+    vy: f32,
+    life: u32,
+}
+
+/// effects that only follow the hits of the world: sparks where a ball bounces,
+/// a trail behind each ball, and a shake and a flash of the screen on a goal;
+/// disabled, nothing is ever shown
+#[derive(Debug, Clone)]
+pub struct Juice {
+    enabled: bool,
+    rng: ChaCha8Rng,
+    sparks: Vec<Spark>,
+    /// the last centres of each ball, the newest first
+    trails: Vec<VecDeque<(i32, i32)>>,
+    shake: u32,
+    offset: (i32, i32),
+    flash: u32,
+}
+
+impl Default for Juice {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl Juice {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            enabled: true,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+            sparks: vec![],
+            trails: vec![],
+            shake: 0,
+            offset: (0, 0),
+            flash: 0,
+        }
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        self.clear();
+    }
+
+    pub fn clear(&mut self) {
+        self.sparks.clear();
+        self.trails.clear();
+        self.shake = 0;
+        self.offset = (0, 0);
+        self.flash = 0;
+    }
+
+    /// to call once for each tick of the world
+    pub fn update(&mut self, world: &World) {
+        if !self.enabled {
+            return;
+        }
+        for spark in &mut self.sparks {
+            spark.x += spark.vx;
+            spark.y += spark.vy;
+            spark.life -= 1;
+        }
+        self.sparks.retain(|spark| spark.life > 0);
+        self.shake = self.shake.saturating_sub(1);
+        self.flash = self.flash.saturating_sub(1);
+        for (hit, (x, y)) in world.hits() {
+            match hit {
+                Hit::Goal(_) => {
+                    self.shake = SHAKE_TICKS;
+                    self.flash = FLASH_TICKS;
+                }
+                _ => self.spark(*x, *y),
+            }
+        }
+        self.offset = if self.shake > 0 {
+            let amplitude = SHAKE_AMPLITUDE * self.shake as i32 / SHAKE_TICKS as i32;
+            (
+                self.rng.gen_range(-amplitude..=amplitude),
+                self.rng.gen_range(-amplitude..=amplitude),
+            )
+        } else {
+            (0, 0)
+        };
+
+        self.trails.resize(world.balls().len(), VecDeque::new());
+        for (trail, ball) in self.trails.iter_mut().zip(world.balls()) {
+            // a ball back at the centre starts a new trail
+            if trail.front().is_some_and(|(x, y)| {
+                (x - ball.center().0).abs() + (y - ball.center().1).abs() > WINDOW_WIDTH as i32 / 4
+            }) {
+                trail.clear();
+            }
+            trail.push_front(ball.center());
+            trail.truncate(TRAIL_LENGTH);
+        }
+    }
+
+    fn spark(&mut self, x: i32, y: i32) {
+        for _ in 0..SPARKS {
+            let angle = self.rng.gen_range(0.0..std::f32::consts::TAU);
+            let speed = self.rng.gen_range(SPARK_SPEED / 2.0..SPARK_SPEED);
+            self.sparks.push(Spark {
+                x: x as f32,
+                y: y as f32,
+                vx: angle.cos() * speed,
+                vy: angle.sin() * speed,
+                life: SPARK_LIFE,
+            });
+        }
+    }
+
+    /// each with its opacity
+    pub fn sparks(&self) -> impl Iterator<Item = (Rect, u8)> + '_ {
+        self.sparks.iter().map(|spark| {
+            let rect = Rect::from_center((spark.x as i32, spark.y as i32), SPARK_SIZE, SPARK_SIZE);
+            (rect, (255 * spark.life / SPARK_LIFE) as u8)
+        })
+    }
+
+    /// smaller and fainter further from the ball, which is not part of it
+    pub fn trail(&self) -> impl Iterator<Item = (Rect, u8)> + '_ {
+        self.trails.iter().flat_map(|trail| {
+            trail.iter().enumerate().skip(1).map(|(i, center)| {
+                let left = (TRAIL_LENGTH - i) as u32;
+                let size = (BALL_DIAMETER * left / TRAIL_LENGTH as u32).max(1);
+                (
+                    Rect::from_center(*center, size, size),
+                    (128 * left / TRAIL_LENGTH as u32) as u8,
+                )
+            })
+        })
+    }
+
+    /// how far the court is moved
+    pub fn offset(&self) -> (i32, i32) {
+        self.offset
+    }
+
+    /// the opacity of the flash over the court
+    pub fn flash(&self) -> u8 {
+        (FLASH_ALPHA as u32 * self.flash / FLASH_TICKS) as u8
+    }
+}
+
+#[test]
+fn test() {
+    use crate::mode::Mode;
+    use crate::paddle::PaddleMove;
+
+    let mut world = World::new(Mode::Classic, 0);
+    let mut juice = Juice::default();
+    let (mut goal, mut wall) = (false, false);
+    for _ in 0..2000 {
+        for team in world.mode().teams() {
+            world.serve(*team);
+        }
+        world.step(&[PaddleMove::Stay; 2]).unwrap();
+        juice.update(&world);
+        if world
+            .hits()
+            .iter()
+            .any(|(hit, _)| matches!(hit, Hit::Goal(_)))
+        {
+            goal = true;
+            assert_eq!(juice.flash(), FLASH_ALPHA);
+            assert!(juice.offset().0.abs() <= SHAKE_AMPLITUDE);
+        }
+        if world.hits().iter().any(|(hit, _)| *hit == Hit::Wall) {
+            wall = true;
+            assert!(juice.sparks().count() >= SPARKS);
+        }
+        assert!(juice.trail().count() < TRAIL_LENGTH * world.balls().len());
+    }
+    assert!(goal && wall);
+
+    juice.set_enabled(false);
+    assert_eq!(juice.sparks().count(), 0);
+    assert_eq!(juice.trail().count(), 0);
+    for _ in 0..200 {
+        world.step(&[PaddleMove::Stay; 2]).unwrap();
+        juice.update(&world);
+        assert_eq!((juice.offset(), juice.flash()), ((0, 0), 0));
+    }
+    assert_eq!(juice.sparks().count(), 0);
+}
@@ -0,0 +1,161 @@
+use crate::net::{step, NetEvent, NetInput, NetSession};
+use crate::packet::PacketReader;
+use crate::world::World;
+use crate::Result;
+use std::collections::VecDeque;
+
+const HISTORY_LEN: usize = 256;
+const STATE_INTERVAL: u32 = 30;
+
+/// the world advances only when the inputs of both players are known;
+/// the host periodically sends its world so the client can correct itself
+pub struct Lockstep {
+    session: NetSession,
+    tick: u32,
+    history: VecDeque<(u32, World, NetInput, NetInput)>,
+    reset_requested: bool,
+}
+
+impl Lockstep {
+    pub fn new(session: NetSession) -> Self {
+        Self {
+            session,
+            tick: 0,
+            history: VecDeque::new(),
+            reset_requested: false,
+        }
+    }
+
+    pub fn session(&self) -> &NetSession {
+        &self.session
+    }
+
+    pub fn session_mut(&mut self) -> &mut NetSession {
+        &mut self.session
+    }
+
+    /// the match restarts on both peers at the same tick
+    pub fn request_reset(&mut self) {
+        self.reset_requested = true;
+    }
+
+    /// returns false when the peer is gone
+    pub fn update(&mut self, world: &mut World, local: NetInput) -> Result<bool> {
+        for event in self.session.poll()? {
+            match event {
+                NetEvent::State(tick, state) => self.reconcile(world, tick, &state)?,
+                NetEvent::Disconnected => return Ok(false),
+            }
+        }
+        if self.session.local_len() <= self.tick + self.session.delay() {
+            self.session.push_local(NetInput {
+                reset: local.reset || self.reset_requested,
+                ..local
+            });
+            self.reset_requested = false;
+        }
+        self.session.send_inputs()?;
+
+        if let Some((input_l, input_r)) = self.session.inputs(self.tick) {
+            if self.history.len() == HISTORY_LEN {
+                self.history.pop_front();
+            }
+            self.history
+                .push_back((self.tick, world.clone(), input_l, input_r));
+            step(world, input_l, input_r)?;
+            self.tick += 1;
+            if self.session.is_host() && self.tick.is_multiple_of(STATE_INTERVAL) {
+                self.session.send_state(self.tick, world)?;
+            }
+        }
+        Ok(true)
+    }
+
+    /// states from the future are dropped: the next one will catch up; so are
+    /// the ones that can't be read
+    fn reconcile(&mut self, world: &mut World, tick: u32, state: &[u8]) -> Result<()> {
+        if tick == self.tick {
+            let mut corrected = world.clone();
+            if corrected.read(&mut PacketReader::new(state)).is_ok() {
+                *world = corrected;
+            }
+            return Ok(());
+        }
+        let first = match self.history.iter().position(|(t, ..)| *t == tick) {
+            Some(first) => first,
+            None => return Ok(()),
+        };
+        let mut corrected = self.history[first].1.clone();
+        if corrected.read(&mut PacketReader::new(state)).is_err()
+            || corrected == self.history[first].1
+        {
+            return Ok(());
+        }
+        for (_, snapshot, input_l, input_r) in self.history.iter_mut().skip(first) {
+            *snapshot = corrected.clone();
+            step(&mut corrected, *input_l, *input_r)?;
+        }
+        *world = corrected;
+        Ok(())
+    }
+}
+
+#[test]
+fn test() {
+    use crate::mode::Mode;
+    use crate::net::NetConfig;
+    use crate::paddle::PaddleMove;
+    use std::time::{Duration, Instant};
+
+    let config = NetConfig {
+        delay: 2,
+        latency: Duration::from_millis(2),
+        loss: 0.2,
+        timeout: Duration::from_secs(5),
+    };
+    let socket = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+    let addr = socket.local_addr().unwrap();
+    drop(socket);
+    let host = std::thread::spawn(move || NetSession::host(addr.port(), config).unwrap());
+    std::thread::sleep(Duration::from_millis(50));
+    let client = NetSession::join(&addr.to_string(), config).unwrap();
+    let host = host.join().unwrap();
+    let mut world_h = World::new(Mode::Classic, host.seed());
+    let mut world_c = World::new(Mode::Classic, client.seed());
+    let mut host = Lockstep::new(host);
+    let mut client = Lockstep::new(client);
+
+    let moves = [PaddleMove::Up, PaddleMove::Stay, PaddleMove::Down];
+    let start = Instant::now();
+    let mut desync = false;
+    let mut i = 0;
+    while host.tick < 200 || client.tick < 200 {
+        assert!(start.elapsed() < Duration::from_secs(10));
+        let input_h = NetInput {
+            movement: moves[i / 20 % 3],
+            reset: false,
+        };
+        let input_c = NetInput {
+            movement: moves[i / 30 % 3],
+            reset: false,
+        };
+        assert!(host.update(&mut world_h, input_h).unwrap());
+        assert!(client.update(&mut world_c, input_c).unwrap());
+        if client.tick == 10 && !desync {
+            world_c.step(&[PaddleMove::Down, PaddleMove::Down]).unwrap();
+            desync = true;
+        }
+        i += 1;
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    for tick in 100..190 {
+        let find = |lockstep: &Lockstep| {
+            lockstep
+                .history
+                .iter()
+                .find(|(t, ..)| *t == tick)
+                .map(|(_, world, ..)| world.clone())
+        };
+        assert_eq!(find(&host).unwrap(), find(&client).unwrap());
+    }
+}
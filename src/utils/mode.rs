@@ -0,0 +1,56 @@
+use crate::team::TeamName;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Mode {
+    Classic,
+    /// two paddles for each team, a back one and a forward one
+    Doubles,
+    /// every wall is a goal, a team is eliminated when it runs out of lives
+    FourPlayer,
+    /// a single paddle against a wall, the points are the consecutive returns
+    Squash,
+}
+
+impl Mode {
+    pub const ALL: [Mode; 4] = [Mode::Classic, Mode::Doubles, Mode::FourPlayer, Mode::Squash];
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "classic" => Some(Mode::Classic),
+            "doubles" => Some(Mode::Doubles),
+            "four" => Some(Mode::FourPlayer),
+            "squash" => Some(Mode::Squash),
+            _ => None,
+        }
+    }
+
+    pub fn teams(self) -> &'static [TeamName] {
+        match self {
+            Mode::Classic | Mode::Doubles => &[TeamName::Left, TeamName::Right],
+            Mode::FourPlayer => &TeamName::ALL,
+            Mode::Squash => &[TeamName::Left],
+        }
+    }
+
+    /// the counters of the point display, in squash the right one is the personal best
+    pub fn counters(self) -> &'static [TeamName] {
+        match self {
+            Mode::Squash => &[TeamName::Left, TeamName::Right],
+            _ => self.teams(),
+        }
+    }
+
+    /// nobody wins, the game goes on until the player leaves
+    pub fn is_solo(self) -> bool {
+        self == Mode::Squash
+    }
+
+    /// teams lose lives instead of scoring points
+    pub fn has_lives(self) -> bool {
+        self == Mode::FourPlayer
+    }
+}
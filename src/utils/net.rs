@@ -0,0 +1,402 @@
+use crate::error::ConnectionTimeout;
+use crate::packet::{PacketReader, PacketWriter};
+use crate::paddle::PaddleMove;
+use crate::team::TeamName;
+use crate::world::World;
+use crate::Result;
+use rand::Rng;
+use std::collections::VecDeque;
+use std::io::ErrorKind;
+use std::net::{SocketAddr, UdpSocket};
+use std::time::{Duration, Instant};
+
+const HELLO: u8 = 0;
+const WELCOME: u8 = 1;
+const INPUT: u8 = 2;
+const STATE: u8 = 3;
+const BYE: u8 = 4;
+
+const MAX_PACKET: usize = 1024;
+const MAX_INPUTS_PER_PACKET: usize = 255;
+const HANDSHAKE_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NetInput {
+    pub movement: PaddleMove,
+    pub reset: bool,
+}
+
+impl Default for NetInput {
+    fn default() -> Self {
+        Self {
+            movement: PaddleMove::Stay,
+            reset: false,
+        }
+    }
+}
+
+impl NetInput {
+    fn to_byte(self) -> u8 {
+        let movement = match self.movement {
+            PaddleMove::Stay => 0,
+            PaddleMove::Up => 1,
+            PaddleMove::Down => 2,
+        };
+        movement | ((self.reset as u8) << 2)
+    }
+
+    fn from_byte(byte: u8) -> Self {
+        let movement = match byte & 0b11 {
+            1 => PaddleMove::Up,
+            2 => PaddleMove::Down,
+            _ => PaddleMove::Stay,
+        };
+        Self {
+            movement,
+            reset: byte & 0b100 != 0,
+        }
+    }
+}
+
+/// `latency` and `loss` are artificial, applied to every packet we send
+#[derive(Debug, Clone, Copy)]
+pub struct NetConfig {
+    pub delay: u32,
+    pub latency: Duration,
+    pub loss: f64,
+    pub timeout: Duration,
+}
+
+#[derive(Debug)]
+pub enum NetEvent {
+    State(u32, Vec<u8>),
+    Disconnected,
+}
+
+/// one peer of a two player match: exchanges the inputs of every tick
+pub struct NetSession {
+    socket: UdpSocket,
+    peer: SocketAddr,
+    side: TeamName,
+    seed: u64,
+    config: NetConfig,
+    local: Vec<NetInput>,
+    remote: Vec<NetInput>,
+    remote_ack: usize,
+    outgoing: VecDeque<(Instant, Vec<u8>)>,
+    last_recv: Instant,
+}
+
+impl NetSession {
+    /// blocks until someone joins, the host controls the left paddle
+    pub fn host(port: u16, config: NetConfig) -> Result<Self> {
+        let socket = UdpSocket::bind(("0.0.0.0", port))?;
+        let seed = rand::thread_rng().gen();
+        let mut buf = [0; MAX_PACKET];
+        let peer = loop {
+            let (len, from) = socket.recv_from(&mut buf)?;
+            if len > 0 && buf[0] == HELLO {
+                break from;
+            }
+        };
+        let mut session = Self::new(socket, peer, TeamName::Left, seed, config)?;
+        session.send_welcome()?;
+        Ok(session)
+    }
+
+    /// the client controls the right paddle
+    pub fn join(addr: &str, config: NetConfig) -> Result<Self> {
+        let socket = UdpSocket::bind(("0.0.0.0", 0))?;
+        socket.connect(addr)?;
+        socket.set_read_timeout(Some(HANDSHAKE_INTERVAL))?;
+        let start = Instant::now();
+        let mut buf = [0; MAX_PACKET];
+        let seed = loop {
+            if start.elapsed() > config.timeout {
+                return Err(Box::new(ConnectionTimeout));
+            }
+            socket.send(&[HELLO])?;
+            match socket.recv(&mut buf) {
+                Ok(len) if len > 0 && buf[0] == WELCOME => {
+                    let mut reader = PacketReader::new(&buf[1..len]);
+                    break reader.get_u64()?;
+                }
+                Ok(_) => {}
+                Err(e) if is_transient(e.kind()) => {}
+                Err(e) => return Err(Box::new(e)),
+            }
+        };
+        let peer = socket.peer_addr()?;
+        Self::new(socket, peer, TeamName::Right, seed, config)
+    }
+
+    fn new(
+        socket: UdpSocket,
+        peer: SocketAddr,
+        side: TeamName,
+        seed: u64,
+        config: NetConfig,
+    ) -> Result<Self> {
+        socket.set_read_timeout(None)?;
+        socket.set_nonblocking(true)?;
+        let delay = config.delay as usize;
+        Ok(Self {
+            socket,
+            peer,
+            side,
+            seed,
+            config,
+            local: vec![NetInput::default(); delay],
+            remote: vec![NetInput::default(); delay],
+            remote_ack: 0,
+            outgoing: VecDeque::new(),
+            last_recv: Instant::now(),
+        })
+    }
+
+    pub fn side(&self) -> TeamName {
+        self.side
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn delay(&self) -> u32 {
+        self.config.delay
+    }
+
+    pub fn is_host(&self) -> bool {
+        self.side == TeamName::Left
+    }
+
+    /// number of ticks for which the local input is known
+    pub fn local_len(&self) -> u32 {
+        self.local.len() as u32
+    }
+
+    pub fn push_local(&mut self, input: NetInput) {
+        self.local.push(input);
+    }
+
+    pub fn local_input(&self, tick: u32) -> Option<NetInput> {
+        self.local.get(tick as usize).copied()
+    }
+
+    /// number of ticks for which the remote input is known
+    pub fn remote_len(&self) -> u32 {
+        self.remote.len() as u32
+    }
+
+    pub fn remote_input(&self, tick: u32) -> Option<NetInput> {
+        self.remote.get(tick as usize).copied()
+    }
+
+    /// inputs of the left and of the right paddle, once both are known
+    pub fn inputs(&self, tick: u32) -> Option<(NetInput, NetInput)> {
+        let local = *self.local.get(tick as usize)?;
+        let remote = *self.remote.get(tick as usize)?;
+        if self.is_host() {
+            Some((local, remote))
+        } else {
+            Some((remote, local))
+        }
+    }
+
+    /// every local input the peer has not confirmed yet is sent again
+    pub fn send_inputs(&mut self) -> Result<()> {
+        let first = self.remote_ack.min(self.local.len());
+        let last = self.local.len().min(first + MAX_INPUTS_PER_PACKET);
+        let mut writer = PacketWriter::new(INPUT);
+        writer.put_u32(self.remote.len() as u32);
+        writer.put_u32(first as u32);
+        writer.put_u8((last - first) as u8);
+        for input in &self.local[first..last] {
+            writer.put_u8(input.to_byte());
+        }
+        self.send(writer.into_bytes())
+    }
+
+    pub fn send_state(&mut self, tick: u32, world: &World) -> Result<()> {
+        let mut writer = PacketWriter::new(STATE);
+        writer.put_u32(tick);
+        world.write(&mut writer);
+        self.send(writer.into_bytes())
+    }
+
+    /// bypasses the artificial latency, the game is about to close
+    pub fn disconnect(&mut self) -> Result<()> {
+        for _ in 0..3 {
+            match self.socket.send_to(&[BYE], self.peer) {
+                Ok(_) => {}
+                Err(e) if is_transient(e.kind()) => {}
+                Err(e) => return Err(Box::new(e)),
+            }
+        }
+        Ok(())
+    }
+
+    pub fn poll(&mut self) -> Result<Vec<NetEvent>> {
+        self.flush()?;
+        let mut events = vec![];
+        let mut buf = [0; MAX_PACKET];
+        loop {
+            let (len, from) = match self.socket.recv_from(&mut buf) {
+                Ok(received) => received,
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) if is_transient(e.kind()) => continue,
+                Err(e) => return Err(Box::new(e)),
+            };
+            if from != self.peer || len == 0 {
+                continue;
+            }
+            self.last_recv = Instant::now();
+            // a malformed datagram is dropped like a lost one
+            let mut reader = PacketReader::new(&buf[1..len]);
+            match buf[0] {
+                HELLO => self.send_welcome()?,
+                INPUT => {
+                    if let Ok((ack, first, inputs)) = read_inputs(&mut reader) {
+                        self.add_inputs(ack, first, &inputs);
+                    }
+                }
+                STATE => {
+                    if let Ok(tick) = reader.get_u32() {
+                        events.push(NetEvent::State(tick, reader.rest().to_vec()));
+                    }
+                }
+                BYE => events.push(NetEvent::Disconnected),
+                _ => {}
+            }
+        }
+        if self.last_recv.elapsed() > self.config.timeout {
+            events.push(NetEvent::Disconnected);
+        }
+        Ok(events)
+    }
+
+    fn add_inputs(&mut self, ack: usize, first: usize, inputs: &[NetInput]) {
+        // the peer can't confirm more than we sent
+        self.remote_ack = self.remote_ack.max(ack.min(self.local.len()));
+        for (tick, input) in (first..).zip(inputs) {
+            if tick == self.remote.len() {
+                self.remote.push(*input);
+            }
+        }
+    }
+
+    fn send_welcome(&mut self) -> Result<()> {
+        let mut writer = PacketWriter::new(WELCOME);
+        writer.put_u64(self.seed);
+        self.socket.send_to(writer.bytes(), self.peer)?;
+        Ok(())
+    }
+
+    fn send(&mut self, packet: Vec<u8>) -> Result<()> {
+        if rand::thread_rng().gen_bool(self.config.loss) {
+            return Ok(());
+        }
+        self.outgoing
+            .push_back((Instant::now() + self.config.latency, packet));
+        self.flush()
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        while let Some((due, _)) = self.outgoing.front() {
+            if *due > Instant::now() {
+                break;
+            }
+            let (_, packet) = self.outgoing.pop_front().expect("It can't be None");
+            match self.socket.send_to(&packet, self.peer) {
+                Ok(_) => {}
+                Err(e) if is_transient(e.kind()) => {}
+                Err(e) => return Err(Box::new(e)),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// the ack, the first tick and the inputs of an INPUT packet
+fn read_inputs(reader: &mut PacketReader) -> Result<(usize, usize, Vec<NetInput>)> {
+    let ack = reader.get_u32()? as usize;
+    let first = reader.get_u32()? as usize;
+    let count = reader.get_u8()? as usize;
+    let inputs = (0..count)
+        .map(|_| Ok(NetInput::from_byte(reader.get_u8()?)))
+        .collect::<Result<_>>()?;
+    Ok((ack, first, inputs))
+}
+
+/// deterministic: the same on every peer
+pub fn step(world: &mut World, input_l: NetInput, input_r: NetInput) -> Result<()> {
+    if input_l.reset || input_r.reset {
+        world.reset();
+    }
+    if world.winner().is_none() {
+        world.step(&[input_l.movement, input_r.movement])?;
+    }
+    Ok(())
+}
+
+/// errors of a single datagram (e.g. the peer is not listening yet)
+fn is_transient(kind: ErrorKind) -> bool {
+    matches!(
+        kind,
+        ErrorKind::WouldBlock
+            | ErrorKind::TimedOut
+            | ErrorKind::ConnectionRefused
+            | ErrorKind::ConnectionReset
+    )
+}
+
+#[test]
+fn test() {
+    let config = NetConfig {
+        delay: 2,
+        latency: Duration::from_millis(5),
+        loss: 0.3,
+        timeout: Duration::from_secs(5),
+    };
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let addr = socket.local_addr().unwrap().to_string();
+    drop(socket);
+    let port = addr.rsplit(':').next().unwrap().parse().unwrap();
+    let host = std::thread::spawn(move || NetSession::host(port, config).unwrap());
+    std::thread::sleep(Duration::from_millis(50));
+    let mut client = NetSession::join(&addr, config).unwrap();
+    let mut host = host.join().unwrap();
+    assert_eq!(host.seed(), client.seed());
+
+    let moves = [PaddleMove::Up, PaddleMove::Stay, PaddleMove::Down];
+    for i in 0..100 {
+        host.push_local(NetInput {
+            movement: moves[i % 3],
+            reset: false,
+        });
+        client.push_local(NetInput {
+            movement: moves[i / 3 % 3],
+            reset: i == 50,
+        });
+    }
+    let start = Instant::now();
+    while host.inputs(101).is_none() || client.inputs(101).is_none() {
+        assert!(start.elapsed() < Duration::from_secs(5));
+        for session in [&mut host, &mut client] {
+            session.poll().unwrap();
+            session.send_inputs().unwrap();
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    for tick in 0..102 {
+        assert_eq!(host.inputs(tick), client.inputs(tick));
+    }
+
+    // truncated packets are ignored
+    for packet in [&[INPUT, 1][..], &[STATE, 0, 0]] {
+        client.socket.send_to(packet, client.peer).unwrap();
+    }
+    std::thread::sleep(Duration::from_millis(20));
+    assert!(host.poll().unwrap().is_empty());
+    assert_eq!(host.remote_len(), 102);
+}
@@ -0,0 +1,49 @@
+use crate::lockstep::Lockstep;
+use crate::net::{NetInput, NetSession};
+use crate::rollback::Rollback;
+use crate::world::World;
+use crate::Result;
+
+pub enum Netcode {
+    Lockstep(Lockstep),
+    Rollback(Rollback),
+}
+
+impl Netcode {
+    pub fn new(session: NetSession, rollback: bool) -> Self {
+        if rollback {
+            Netcode::Rollback(Rollback::new(session))
+        } else {
+            Netcode::Lockstep(Lockstep::new(session))
+        }
+    }
+
+    pub fn session(&self) -> &NetSession {
+        match self {
+            Netcode::Lockstep(lockstep) => lockstep.session(),
+            Netcode::Rollback(rollback) => rollback.session(),
+        }
+    }
+
+    pub fn session_mut(&mut self) -> &mut NetSession {
+        match self {
+            Netcode::Lockstep(lockstep) => lockstep.session_mut(),
+            Netcode::Rollback(rollback) => rollback.session_mut(),
+        }
+    }
+
+    pub fn request_reset(&mut self) {
+        match self {
+            Netcode::Lockstep(lockstep) => lockstep.request_reset(),
+            Netcode::Rollback(rollback) => rollback.request_reset(),
+        }
+    }
+
+    /// returns false when the peer is gone
+    pub fn update(&mut self, world: &mut World, local: NetInput) -> Result<bool> {
+        match self {
+            Netcode::Lockstep(lockstep) => lockstep.update(world, local),
+            Netcode::Rollback(rollback) => rollback.update(world, local),
+        }
+    }
+}
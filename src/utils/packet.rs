@@ -0,0 +1,94 @@
+use crate::error::PacketTooShort;
+use crate::Result;
+
+/// little endian encoding of the messages exchanged over the network
+#[derive(Debug)]
+pub struct PacketWriter {
+    buf: Vec<u8>,
+}
+
+impl PacketWriter {
+    pub fn new(kind: u8) -> Self {
+        Self { buf: vec![kind] }
+    }
+    pub fn put_u8(&mut self, value: u8) {
+        self.buf.push(value);
+    }
+    pub fn put_u16(&mut self, value: u16) {
+        self.buf.extend_from_slice(&value.to_le_bytes());
+    }
+    pub fn put_u32(&mut self, value: u32) {
+        self.buf.extend_from_slice(&value.to_le_bytes());
+    }
+    pub fn put_i32(&mut self, value: i32) {
+        self.buf.extend_from_slice(&value.to_le_bytes());
+    }
+    pub fn put_u64(&mut self, value: u64) {
+        self.buf.extend_from_slice(&value.to_le_bytes());
+    }
+    pub fn put_u128(&mut self, value: u128) {
+        self.buf.extend_from_slice(&value.to_le_bytes());
+    }
+    pub fn bytes(&self) -> &[u8] {
+        &self.buf
+    }
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.buf
+    }
+}
+
+#[derive(Debug)]
+pub struct PacketReader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> PacketReader<'a> {
+    pub fn new(buf: &'a [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.pos >= self.buf.len()
+    }
+    pub fn rest(&self) -> &'a [u8] {
+        &self.buf[self.pos.min(self.buf.len())..]
+    }
+    fn take<const N: usize>(&mut self) -> Result<[u8; N]> {
+        let bytes = self.buf.get(self.pos..self.pos + N).ok_or(PacketTooShort)?;
+        self.pos += N;
+        Ok(bytes.try_into()?)
+    }
+    pub fn get_u8(&mut self) -> Result<u8> {
+        Ok(u8::from_le_bytes(self.take()?))
+    }
+    pub fn get_u16(&mut self) -> Result<u16> {
+        Ok(u16::from_le_bytes(self.take()?))
+    }
+    pub fn get_u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.take()?))
+    }
+    pub fn get_i32(&mut self) -> Result<i32> {
+        Ok(i32::from_le_bytes(self.take()?))
+    }
+    pub fn get_u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.take()?))
+    }
+    pub fn get_u128(&mut self) -> Result<u128> {
+        Ok(u128::from_le_bytes(self.take()?))
+    }
+}
+
+#[test]
+fn test() {
+    let mut writer = PacketWriter::new(7);
+    writer.put_i32(-3);
+    writer.put_u16(513);
+    writer.put_u128(u128::MAX - 1);
+    let mut reader = PacketReader::new(writer.bytes());
+    assert_eq!(reader.get_u8().unwrap(), 7);
+    assert_eq!(reader.get_i32().unwrap(), -3);
+    assert_eq!(reader.get_u16().unwrap(), 513);
+    assert_eq!(reader.get_u128().unwrap(), u128::MAX - 1);
+    assert!(reader.is_empty());
+    assert!(reader.get_u8().is_err());
+}
@@ -1,20 +1,47 @@
 use crate::ball::Ball;
 use crate::segment::Segmet1D;
+use crate::team::TeamName;
 use sdl2::rect::Rect;
 
-#[derive(Debug)]
+/// horizontal paddles go left with `Up` and right with `Down`
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PaddleMove {
+    Up,
+    Down,
+    Stay,
+}
+
+impl PaddleMove {
+    pub fn inverted(self) -> Self {
+        match self {
+            PaddleMove::Up => PaddleMove::Down,
+            PaddleMove::Down => PaddleMove::Up,
+            PaddleMove::Stay => PaddleMove::Stay,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Paddle {
+    team: TeamName,
     rect: Rect,
     step: u32,
 }
 
 impl Paddle {
-    pub fn new(x: i32, y: i32, width: u32, height: u32, step: u32) -> Self {
+    pub fn new(team: TeamName, x: i32, y: i32, width: u32, height: u32, step: u32) -> Self {
         Self {
+            team,
             rect: Rect::new(x, y, width, height),
             step,
         }
     }
+    pub fn team(&self) -> TeamName {
+        self.team
+    }
+    pub fn is_horizontal(&self) -> bool {
+        self.team.is_horizontal()
+    }
     pub fn x(&self) -> i32 {
         self.rect.x()
     }
@@ -33,6 +60,59 @@ impl Paddle {
     fn step(&self) -> i32 {
         self.step as i32
     }
+    pub fn set_position(&mut self, x: i32, y: i32) {
+        self.rect.x = x;
+        self.rect.y = y;
+    }
+
+    /// along the direction the paddle moves
+    pub fn length(&self) -> u32 {
+        if self.is_horizontal() {
+            self.rect.width()
+        } else {
+            self.rect.height()
+        }
+    }
+
+    /// keeps the centre where it is, without crossing `min` and `max`
+    pub fn set_length(&mut self, length: u32, min: i32, max: i32) {
+        let length = length.min((max - min) as u32);
+        if self.is_horizontal() {
+            let center = self.x() + self.width() / 2;
+            self.rect.set_width(length);
+            self.rect.x = (center - length as i32 / 2).clamp(min, max - length as i32);
+        } else {
+            let center = self.y() + self.height() / 2;
+            self.rect.set_height(length);
+            self.rect.y = (center - length as i32 / 2).clamp(min, max - length as i32);
+        }
+    }
+
+    /// `min` and `max` are the limits along the direction the paddle moves;
+    /// every ball in the way is pushed, the paddle stops at the one that blocks it first
+    pub fn apply(&mut self, movement: PaddleMove, min: i32, max: i32, balls: &mut [Ball]) {
+        let start = self.rect;
+        let mut end = None;
+        for ball in balls.iter_mut() {
+            self.rect = start;
+            self.apply_ball(movement, min, max, ball);
+            let distance = |rect: Rect| (rect.x() - start.x()).abs() + (rect.y() - start.y()).abs();
+            if end.is_none_or(|end| distance(self.rect) < distance(end)) {
+                end = Some(self.rect);
+            }
+        }
+        self.rect = end.unwrap_or(start);
+    }
+
+    fn apply_ball(&mut self, movement: PaddleMove, min: i32, max: i32, ball: &mut Ball) {
+        match (movement, self.is_horizontal()) {
+            (PaddleMove::Up, false) => self.move_up(min, ball),
+            (PaddleMove::Down, false) => self.move_down(max, ball),
+            (PaddleMove::Up, true) => self.move_left(min, ball),
+            (PaddleMove::Down, true) => self.move_right(max, ball),
+            (PaddleMove::Stay, _) => {}
+        }
+    }
 
     pub fn move_up(&mut self, top: i32, ball: &mut Ball) {
         let ball_bottom = ball.y() + ball.height();
@@ -47,7 +127,7 @@ impl Paddle {
 
         if (paddle_top >= ball_bottom && ball_bottom > paddle_top_virtual) && s1.intersect(&s2) {
             let virtual_ball_pos = paddle_top_virtual - ball.height();
-            let step = (ball.y() - virtual_ball_pos).abs() as u32;
+            let step = (ball.y() - virtual_ball_pos).unsigned_abs();
             ball.move_up(step, top);
             let ball_bottom = ball.y() + ball.height();
             self.rect.y = ball_bottom;
@@ -71,14 +151,62 @@ impl Paddle {
 
         if (paddle_bottom <= ball_top && ball_top < paddle_bottom_virtual) && s1.intersect(&s2) {
             let virtual_ball_pos = paddle_bottom_virtual;
-            let step = (ball.y() - virtual_ball_pos).abs() as u32;
+            let step = (ball.y() - virtual_ball_pos).unsigned_abs();
             ball.move_down(step, bottom);
             let ball_top = ball.y();
             self.rect.y = ball_top - self.height();
-        } else if self.y() + self.height() + self.step() <= bottom as i32 {
+        } else if self.y() + self.height() + self.step() <= bottom {
             self.rect.y = paddle_bottom_virtual - self.height();
         } else {
             self.rect.y = bottom - self.height();
         }
     }
+
+    pub fn move_left(&mut self, left: i32, ball: &mut Ball) {
+        let ball_right = ball.x() + ball.width();
+        let ball_top = ball.y();
+        let ball_bottom = ball.y() + ball.height();
+        let paddle_left = self.x();
+        let paddle_left_virtual = self.x() - self.step();
+        let paddle_top = self.y();
+        let paddle_bottom = self.y() + self.height();
+        let s1 = Segmet1D::new(ball_top, ball_bottom);
+        let s2 = Segmet1D::new(paddle_top, paddle_bottom);
+
+        if (paddle_left >= ball_right && ball_right > paddle_left_virtual) && s1.intersect(&s2) {
+            let virtual_ball_pos = paddle_left_virtual - ball.width();
+            let step = (ball.x() - virtual_ball_pos).unsigned_abs();
+            ball.move_left(step, left);
+            let ball_right = ball.x() + ball.width();
+            self.rect.x = ball_right;
+        } else if paddle_left_virtual >= left {
+            self.rect.x = paddle_left_virtual;
+        } else {
+            self.rect.x = left;
+        }
+    }
+
+    pub fn move_right(&mut self, right: i32, ball: &mut Ball) {
+        let ball_left = ball.x();
+        let ball_top = ball.y();
+        let ball_bottom = ball.y() + ball.height();
+        let paddle_right = self.x() + self.width();
+        let paddle_right_virtual = self.x() + self.width() + self.step();
+        let paddle_top = self.y();
+        let paddle_bottom = self.y() + self.height();
+        let s1 = Segmet1D::new(ball_top, ball_bottom);
+        let s2 = Segmet1D::new(paddle_top, paddle_bottom);
+
+        if (paddle_right <= ball_left && ball_left < paddle_right_virtual) && s1.intersect(&s2) {
+            let virtual_ball_pos = paddle_right_virtual;
+            let step = (ball.x() - virtual_ball_pos).unsigned_abs();
+            ball.move_right(step, right);
+            let ball_left = ball.x();
+            self.rect.x = ball_left - self.width();
+        } else if paddle_right_virtual <= right {
+            self.rect.x = paddle_right_virtual - self.width();
+        } else {
+            self.rect.x = right - self.width();
+        }
+    }
 }
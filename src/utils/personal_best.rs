@@ -0,0 +1,75 @@
+use crate::constants::*;
+use crate::Result;
+use std::path::PathBuf;
+
+/// the longest squash streak, kept in a file between sessions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PersonalBest {
+    path: PathBuf,
+    best: u32,
+    /// the record in the file
+    saved: u32,
+}
+
+impl PersonalBest {
+    /// a missing or unreadable file counts as no record
+    pub fn load(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let best = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|text| text.trim().parse().ok())
+            .unwrap_or(0);
+        Self {
+            path,
+            best,
+            saved: best,
+        }
+    }
+
+    /// `SQUASH_BEST_FILE` in the home directory, or in the current one
+    pub fn default_path() -> PathBuf {
+        let home = std::env::var_os("HOME").map_or_else(PathBuf::new, PathBuf::from);
+        home.join(SQUASH_BEST_FILE)
+    }
+
+    pub fn best(&self) -> u32 {
+        self.best
+    }
+
+    /// keeps `score` if it beats the record, returns true if it did; the file
+    /// is only written by `save`
+    pub fn update(&mut self, score: u32) -> bool {
+        if score <= self.best {
+            return false;
+        }
+        self.best = score;
+        true
+    }
+
+    /// writes the record if it has changed since the last time
+    pub fn save(&mut self) -> Result<()> {
+        if self.best != self.saved {
+            std::fs::write(&self.path, format!("{}\n", self.best))?;
+            self.saved = self.best;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test() {
+    let path = std::env::temp_dir().join(format!("pong_best_{}", std::process::id()));
+    let mut best = PersonalBest::load(&path);
+    assert_eq!(best.best(), 0);
+    assert!(best.update(7));
+    assert!(!best.update(5));
+    assert_eq!(PersonalBest::load(&path).best(), 0);
+    best.save().unwrap();
+    assert_eq!(PersonalBest::load(&path).best(), 7);
+    // nothing to write, even where it can't be
+    best.path = std::env::temp_dir().join("nowhere").join("best");
+    best.save().unwrap();
+    best.update(8);
+    assert!(best.save().is_err());
+    std::fs::remove_file(path).unwrap();
+}
@@ -1,3 +1,4 @@
+use sdl2::pixels::Color;
 use sdl2::rect::Rect;
 use sdl2::render::Canvas;
 use sdl2::render::Texture;
@@ -7,19 +8,106 @@ use sdl2::video::{Window, WindowContext};
 use crate::constants::*;
 use crate::team::TeamName;
 use crate::Result;
-use std::collections::{HashMap, VecDeque};
+use std::collections::HashMap;
 use std::rc::Rc;
 
-const X_PIXEL: u32 = 5;
-const Y_PIXEL: u32 = 7;
+pub const X_PIXEL: u32 = 5;
+pub const Y_PIXEL: u32 = 7;
 
+/// the pixels of a character, one bit for each from the top left corner
+pub const GLYPHS: [(char, u64); 10] = [
+    ('0', 0b01110100011001110101110011000101110),
+    ('1', 0b01110001000010000100001000011000100),
+    ('2', 0b11111000010001001100100001000101110),
+    ('3', 0b01110100011000001100100001000101110),
+    ('4', 0b01000010001111101001010100110001000),
+    ('5', 0b01110100011000010000011110000111111),
+    ('6', 0b01110100011000101111000011000101110),
+    ('7', 0b00001000010001000100010001000011111),
+    ('8', 0b01110100011000101110100011000101110),
+    ('9', 0b01110100011000011110100011000101110),
+    /* ('A', 0b10001100011000111111100011000111110),
+    ('B', 0b01111100011000101111100011000101111),
+    ('C', 0b01110100010000100001000011000101110),
+    ('D', 0b01111100011000110001100011000101111),
+    ('E', 0b11111000010000101111000010000111111),
+    ('F', 0b00001000010000101111000010000111111),
+    ('G', 0b01110100011100100001000011000101110),
+    ('H', 0b10001100011000111111100011000110001),
+    ('I', 0b01110001000010000100001000010001110),
+    ('J', 0b01110100011000110000100001000011000),
+    ('K', 0b10001010010010100011001010100110001),
+    ('L', 0b11111000010000100001000010000100001),
+    ('M', 0b10001100011000110001101011101110001),
+    ('N', 0b10001100011100110101100111000110001),
+    ('O', 0b01110100011000110001100011000101110),
+    ('P', 0b00001000010000101111100011000101111),
+    ('Q', 0b10110010011010110001100011000101110),
+    ('R', 0b10001100011000101111100011000101111),
+    ('S', 0b01110100011000001110000011000101110),
+    ('T', 0b00100001000010000100001000010011111),
+    ('U', 0b01110100011000110001100011000110001),
+    ('V', 0b00100010101000110001100011000110001),
+    ('W', 0b01010101011010110001100011000110001),
+    ('X', 0b10001100010101000100010101000110001),
+    ('Y', 0b00100001000010000100010101000110001),
+    ('Z', 0b11111000010001000100010001000011111), */
+];
+
+/// the digits of a counter grow away from `x`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Right,
+}
+
+struct Counter<'a> {
+    team: TeamName,
+    points: u32,
+    x: i32,
+    y: i32,
+    align: Align,
+    /// size of a pixel of a digit
+    scale: u32,
+    textures: Vec<Rc<Texture<'a>>>,
+    rects: Vec<Rect>,
+    /// ticks left of the growth after a change
+    pop: u32,
+}
+
+impl Counter<'_> {
+    fn new(team: TeamName, (x, y, align): (i32, i32, Align), scale: u32) -> Self {
+        Counter {
+            team,
+            points: 0,
+            x,
+            y,
+            align,
+            scale,
+            textures: vec![],
+            rects: vec![],
+            pop: 0,
+        }
+    }
+}
+
+/// one counter for each team: next to the mid line in a two teams match,
+/// next to the team's own wall otherwise; the sets won and the time left
+/// are smaller and only drawn when they are shown, like the frames per second
+/// and the frame time of the debug overlay; an animated counter grows for a
+/// moment when its points change
 pub struct PointDisplay<'a> {
-    point_left: u32,
-    point_right: u32,
-    textures_left: Vec<Rc<Texture<'a>>>,
-    textures_right: Vec<Rc<Texture<'a>>>,
-    rects_left: VecDeque<Rect>,
-    rects_right: VecDeque<Rect>,
+    counters: Vec<Counter<'a>>,
+    sets: Vec<Counter<'a>>,
+    clock: Counter<'a>,
+    /// frames per second, then frame time in milliseconds
+    stats: [Counter<'a>; 2],
+    /// large in the middle of the court, hidden at 0
+    countdown: Counter<'a>,
+    show_sets: bool,
+    show_clock: bool,
+    show_stats: bool,
+    animate: bool,
     textures_hm: HashMap<char, Rc<Texture<'a>>>,
 }
 
@@ -27,119 +115,244 @@ impl<'a> PointDisplay<'a> {
     pub fn new(
         texture_creator: &'a TextureCreator<WindowContext>,
         canvas: &mut Canvas<Window>,
+        teams: &[TeamName],
+        color: Color,
+        background_color: Color,
     ) -> Result<Self> {
+        let (counters, sets) = team_counters(teams);
+        let top = DISPLAY_COEFFICENT as i32;
+        let clock = Counter::new(
+            TeamName::Left,
+            (top, top, Align::Left),
+            DISPLAY_COEFFICENT / 2,
+        );
+        let right = WINDOW_WIDTH as i32 - top;
+        let below = top + ((Y_PIXEL + 2) * DISPLAY_COEFFICENT / 2) as i32;
+        let stats = [top, below].map(|y| {
+            Counter::new(
+                TeamName::Right,
+                (right, y, Align::Right),
+                DISPLAY_COEFFICENT / 2,
+            )
+        });
+        let scale = DISPLAY_COEFFICENT * 2;
+        let countdown = Counter::new(
+            TeamName::Left,
+            (
+                ((WINDOW_WIDTH - X_PIXEL * scale) / 2) as i32,
+                ((WINDOW_HEIGHT - Y_PIXEL * scale) / 2) as i32,
+                Align::Left,
+            ),
+            scale,
+        );
         let mut point_display = Self {
-            point_left: 0,
-            point_right: 0,
-            textures_left: vec![],
-            textures_right: vec![],
-            rects_left: VecDeque::new(),
-            rects_right: VecDeque::new(),
-            textures_hm: create_all_texture(canvas, texture_creator)?,
+            counters,
+            sets,
+            clock,
+            stats,
+            countdown,
+            show_sets: false,
+            show_clock: false,
+            show_stats: false,
+            animate: false,
+            textures_hm: create_all_texture(canvas, texture_creator, color, background_color)?,
         };
         point_display.reset()?;
         Ok(point_display)
     }
 
     pub fn reset(&mut self) -> Result<()> {
-        let points = vec!['0'];
-        self.point_left = 0;
-        self.update_textures(TeamName::Left, &points)?;
-        self.update_rects(TeamName::Left, &points)?;
-        self.point_right = 0;
-        self.update_textures(TeamName::Right, &points)?;
-        self.update_rects(TeamName::Right, &points)?;
-        Ok(())
+        let counters = self.counters.iter_mut().chain(&mut self.sets);
+        let counters = counters.chain(&mut self.stats);
+        for counter in counters.chain([&mut self.countdown]) {
+            update(counter, &self.textures_hm, 0)?;
+        }
+        update(&mut self.clock, &self.textures_hm, 0)
     }
 
-    pub fn left(&mut self) -> u32 {
-        self.point_left
+    /// the counters of other teams, all back to 0
+    pub fn set_teams(&mut self, teams: &[TeamName]) -> Result<()> {
+        (self.counters, self.sets) = team_counters(teams);
+        self.reset()
     }
 
-    pub fn right(&mut self) -> u32 {
-        self.point_right
+    pub fn show_sets(&mut self, show: bool) {
+        self.show_sets = show;
     }
 
-    pub fn incr_point(&mut self, team: TeamName) -> Result<()> {
-        let digits: Vec<_> = match team {
-            TeamName::Left => {
-                self.point_left += 1;
-                self.point_left.to_string().chars().collect()
-            }
-            TeamName::Right => {
-                self.point_right += 1;
-                self.point_right.to_string().chars().collect()
-            }
-        };
-        self.update_textures(team, &digits)?;
-        self.update_rects(team, &digits)
+    pub fn show_clock(&mut self, show: bool) {
+        self.show_clock = show;
     }
 
-    pub fn draw(&self, canvas: &mut Canvas<Window>) -> Result<()> {
-        for (texture, rect) in self.textures_left.iter().zip(self.rects_left.iter()) {
-            canvas.copy(texture, None, *rect)?;
+    pub fn show_stats(&mut self, show: bool) {
+        self.show_stats = show;
+    }
+
+    pub fn set_animate(&mut self, animate: bool) {
+        self.animate = animate;
+    }
+
+    /// to call once for each tick, it moves the animation of the counters
+    pub fn tick(&mut self) {
+        for counter in &mut self.counters {
+            counter.pop = counter.pop.saturating_sub(1);
         }
-        for (texture, rect) in self.textures_right.iter().zip(self.rects_right.iter()) {
-            canvas.copy(texture, None, *rect)?;
+    }
+
+    /// `frame_time` in milliseconds
+    pub fn set_stats(&mut self, fps: u32, frame_time: u32) -> Result<()> {
+        for (counter, value) in self.stats.iter_mut().zip([fps, frame_time]) {
+            if counter.points != value {
+                update(counter, &self.textures_hm, value)?;
+            }
         }
         Ok(())
     }
 
-    fn update_textures(&mut self, team: TeamName, digits: &Vec<char>) -> Result<()> {
-        match team {
-            TeamName::Left => {
-                overwrite_textures(&mut self.textures_left, &self.textures_hm, digits)
-            }
-            TeamName::Right => {
-                overwrite_textures(&mut self.textures_right, &self.textures_hm, digits)
-            }
+    pub fn set_sets(&mut self, team: TeamName, sets: u32) -> Result<()> {
+        match self.sets.iter_mut().find(|counter| counter.team == team) {
+            Some(counter) if counter.points != sets => update(counter, &self.textures_hm, sets),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn countdown(&self) -> u32 {
+        self.countdown.points
+    }
+
+    /// in seconds, 0 hides it
+    pub fn set_countdown(&mut self, seconds: u32) -> Result<()> {
+        if self.countdown.points == seconds {
+            return Ok(());
         }
+        update(&mut self.countdown, &self.textures_hm, seconds)
     }
 
-    fn update_rects(&mut self, team: TeamName, digits: &Vec<char>) -> Result<()> {
-        match team {
-            TeamName::Left => overwrite_rects_left(&mut self.rects_left, digits),
-            TeamName::Right => overwrite_rects_right(&mut self.rects_right, digits),
+    /// in seconds
+    pub fn set_clock(&mut self, seconds: u32) -> Result<()> {
+        if self.clock.points == seconds {
+            return Ok(());
         }
+        update(&mut self.clock, &self.textures_hm, seconds)
     }
-}
 
-fn overwrite_rects_right(rects: &mut VecDeque<Rect>, digits: &Vec<char>) -> Result<()> {
-    if rects.len() > digits.len() {
-        rects.drain(rects.len() - digits.len()..);
+    pub fn set_point(&mut self, team: TeamName, points: u32) -> Result<()> {
+        match self
+            .counters
+            .iter_mut()
+            .find(|counter| counter.team == team)
+        {
+            Some(counter) if counter.points != points => {
+                if self.animate {
+                    counter.pop = SCORE_POP_TICKS;
+                }
+                update(counter, &self.textures_hm, points)
+            }
+            _ => Ok(()),
+        }
     }
-    for i in rects.len()..digits.len() {
-        rects.push_back(Rect::new(
-            ((WINDOW_WIDTH + MID_LINE_WIDTH) / 2
-                + DISPLAY_COEFFICENT * (1 + i as u32 * (1 + X_PIXEL))) as i32,
-            DISPLAY_COEFFICENT as i32,
-            DISPLAY_COEFFICENT * X_PIXEL,
-            DISPLAY_COEFFICENT * Y_PIXEL,
-        ));
+
+    pub fn draw(&self, canvas: &mut Canvas<Window>) -> Result<()> {
+        let sets = self.sets.iter().filter(|_| self.show_sets);
+        let clock = std::iter::once(&self.clock).filter(|_| self.show_clock);
+        let stats = self.stats.iter().filter(|_| self.show_stats);
+        let countdown = std::iter::once(&self.countdown).filter(|counter| counter.points > 0);
+        let counters = self.counters.iter().chain(sets).chain(clock);
+        for counter in counters.chain(stats).chain(countdown) {
+            for (texture, rect) in counter.textures.iter().zip(counter.rects.iter()) {
+                canvas.copy(texture, None, popped(*rect, counter.pop))?;
+            }
+        }
+        Ok(())
     }
+}
+
+/// the points then the sets of each team
+fn team_counters<'a>(teams: &[TeamName]) -> (Vec<Counter<'a>>, Vec<Counter<'a>>) {
+    let counters = teams
+        .iter()
+        .map(|team| Counter::new(*team, anchor(*team, teams.len() > 2), DISPLAY_COEFFICENT))
+        .collect();
+    let sets = teams
+        .iter()
+        .map(|team| {
+            let (x, y, align) = anchor(*team, teams.len() > 2);
+            let y = y + ((Y_PIXEL + 2) * DISPLAY_COEFFICENT) as i32;
+            Counter::new(*team, (x, y, align), DISPLAY_COEFFICENT / 2)
+        })
+        .collect();
+    (counters, sets)
+}
+
+fn update<'a>(
+    counter: &mut Counter<'a>,
+    textures_hm: &HashMap<char, Rc<Texture<'a>>>,
+    points: u32,
+) -> Result<()> {
+    let digits: Vec<_> = points.to_string().chars().collect();
+    counter.points = points;
+    overwrite_textures(&mut counter.textures, textures_hm, &digits)?;
+    overwrite_rects(counter, digits.len());
     Ok(())
 }
 
-fn overwrite_rects_left(rects: &mut VecDeque<Rect>, digits: &Vec<char>) -> Result<()> {
-    if rects.len() > digits.len() {
-        rects.drain(..digits.len());
-    }
-    for i in rects.len()..digits.len() {
-        rects.push_front(Rect::new(
-            (WINDOW_WIDTH - MID_LINE_WIDTH) as i32 / 2
-                - (DISPLAY_COEFFICENT * (i + 1) as u32 * (X_PIXEL + 1)) as i32,
-            DISPLAY_COEFFICENT as i32,
-            DISPLAY_COEFFICENT * X_PIXEL,
-            DISPLAY_COEFFICENT * Y_PIXEL,
-        ));
+/// grown around its centre, the most right after the change
+pub fn popped(rect: Rect, pop: u32) -> Rect {
+    let grow = 1.0 + SCORE_POP_GROW * pop as f32 / SCORE_POP_TICKS as f32;
+    let width = (rect.width() as f32 * grow) as u32;
+    let height = (rect.height() as f32 * grow) as u32;
+    Rect::from_center(rect.center(), width, height)
+}
+
+/// where the counter of `team` starts, in window coordinates
+pub fn anchor(team: TeamName, four_teams: bool) -> (i32, i32, Align) {
+    let top = DISPLAY_COEFFICENT as i32;
+    let right_of_mid_line = ((WINDOW_WIDTH + MID_LINE_WIDTH) / 2 + DISPLAY_COEFFICENT) as i32;
+    let middle = (WINDOW_HEIGHT - DISPLAY_COEFFICENT * Y_PIXEL) as i32 / 2;
+    match (team, four_teams) {
+        (TeamName::Left, false) => (
+            (WINDOW_WIDTH - MID_LINE_WIDTH) as i32 / 2,
+            top,
+            Align::Right,
+        ),
+        (TeamName::Right, false) => (right_of_mid_line, top, Align::Left),
+        (TeamName::Left, true) => ((WINDOW_WIDTH / 8) as i32, middle, Align::Left),
+        (TeamName::Right, true) => (
+            (WINDOW_WIDTH - WINDOW_WIDTH / 8) as i32,
+            middle,
+            Align::Right,
+        ),
+        (TeamName::Top, _) => (right_of_mid_line, (WINDOW_HEIGHT / 8) as i32, Align::Left),
+        (TeamName::Bottom, _) => (
+            right_of_mid_line,
+            (WINDOW_HEIGHT - WINDOW_HEIGHT / 8 - DISPLAY_COEFFICENT * Y_PIXEL) as i32,
+            Align::Left,
+        ),
     }
-    Ok(())
+}
+
+fn overwrite_rects(counter: &mut Counter, digits: usize) {
+    let advance = (counter.scale * (X_PIXEL + 1)) as i32;
+    counter.rects = (0..digits as i32)
+        .map(|i| {
+            let x = match counter.align {
+                Align::Left => counter.x + i * advance,
+                Align::Right => counter.x - (digits as i32 - i) * advance,
+            };
+            Rect::new(
+                x,
+                counter.y,
+                counter.scale * X_PIXEL,
+                counter.scale * Y_PIXEL,
+            )
+        })
+        .collect();
 }
 
 fn overwrite_textures<'a>(
     textures: &mut Vec<Rc<Texture<'a>>>,
     textures_hm: &HashMap<char, Rc<Texture<'a>>>,
-    digits: &Vec<char>,
+    digits: &[char],
 ) -> Result<()> {
     textures.resize(
         digits.len(),
@@ -162,53 +375,16 @@ fn overwrite_textures<'a>(
 fn create_all_texture<'a>(
     canvas: &mut Canvas<Window>,
     texture_creator: &'a TextureCreator<WindowContext>,
+    color: Color,
+    background_color: Color,
 ) -> Result<HashMap<char, Rc<Texture<'a>>>> {
-    let display_char: [(char, u64); 10] = [
-        ('0', 0b01110100011001110101110011000101110),
-        ('1', 0b01110001000010000100001000011000100),
-        ('2', 0b11111000010001001100100001000101110),
-        ('3', 0b01110100011000001100100001000101110),
-        ('4', 0b01000010001111101001010100110001000),
-        ('5', 0b01110100011000010000011110000111111),
-        ('6', 0b01110100011000101111000011000101110),
-        ('7', 0b00001000010001000100010001000011111),
-        ('8', 0b01110100011000101110100011000101110),
-        ('9', 0b01110100011000011110100011000101110),
-        /* ('A', 0b10001100011000111111100011000111110),
-        ('B', 0b01111100011000101111100011000101111),
-        ('C', 0b01110100010000100001000011000101110),
-        ('D', 0b01111100011000110001100011000101111),
-        ('E', 0b11111000010000101111000010000111111),
-        ('F', 0b00001000010000101111000010000111111),
-        ('G', 0b01110100011100100001000011000101110),
-        ('H', 0b10001100011000111111100011000110001),
-        ('I', 0b01110001000010000100001000010001110),
-        ('J', 0b01110100011000110000100001000011000),
-        ('K', 0b10001010010010100011001010100110001),
-        ('L', 0b11111000010000100001000010000100001),
-        ('M', 0b10001100011000110001101011101110001),
-        ('N', 0b10001100011100110101100111000110001),
-        ('O', 0b01110100011000110001100011000101110),
-        ('P', 0b00001000010000101111100011000101111),
-        ('Q', 0b10110010011010110001100011000101110),
-        ('R', 0b10001100011000101111100011000101111),
-        ('S', 0b01110100011000001110000011000101110),
-        ('T', 0b00100001000010000100001000010011111),
-        ('U', 0b01110100011000110001100011000110001),
-        ('V', 0b00100010101000110001100011000110001),
-        ('W', 0b01010101011010110001100011000110001),
-        ('X', 0b10001100010101000100010101000110001),
-        ('Y', 0b00100001000010000100010101000110001),
-        ('Z', 0b11111000010001000100010001000011111), */
-    ];
-
     let mut hm = HashMap::new();
-    for (c, mut x) in display_char {
+    for (c, mut x) in GLYPHS {
         let mut texture = texture_creator.create_texture_target(None, X_PIXEL, Y_PIXEL)?;
         canvas.with_texture_canvas(&mut texture, |texture_canvas| {
-            texture_canvas.set_draw_color(BACKGROUND_COLOR);
+            texture_canvas.set_draw_color(background_color);
             texture_canvas.clear();
-            texture_canvas.set_draw_color(DISPLAY_COLOR);
+            texture_canvas.set_draw_color(color);
             for i in 0..Y_PIXEL as i32 {
                 for j in 0..X_PIXEL as i32 {
                     if x & 1 == 1 {
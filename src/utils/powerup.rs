@@ -0,0 +1,248 @@
+use crate::constants::*;
+use crate::error::InvalidPacket;
+use crate::packet::{PacketReader, PacketWriter};
+use crate::segment::Segmet1D;
+use crate::Result;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+
+/// what a pickup does to the team of the paddle that last hit the ball
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum PowerUpKind {
+    /// the paddle gets longer
+    Grow,
+    /// the paddles of the other teams get shorter
+    Shrink,
+    /// every ball gets faster
+    SpeedUp,
+    /// every ball gets slower
+    SlowDown,
+    /// one more ball in play
+    MultiBall,
+    /// the paddle holds the ball for a moment before releasing it
+    Sticky,
+    /// the other teams move the wrong way
+    Invert,
+}
+
+impl PowerUpKind {
+    pub const ALL: [PowerUpKind; 7] = [
+        PowerUpKind::Grow,
+        PowerUpKind::Shrink,
+        PowerUpKind::SpeedUp,
+        PowerUpKind::SlowDown,
+        PowerUpKind::MultiBall,
+        PowerUpKind::Sticky,
+        PowerUpKind::Invert,
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "grow" => Some(PowerUpKind::Grow),
+            "shrink" => Some(PowerUpKind::Shrink),
+            "fast" => Some(PowerUpKind::SpeedUp),
+            "slow" => Some(PowerUpKind::SlowDown),
+            "multi" => Some(PowerUpKind::MultiBall),
+            "sticky" => Some(PowerUpKind::Sticky),
+            "invert" => Some(PowerUpKind::Invert),
+            _ => None,
+        }
+    }
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn from_index(index: usize) -> Option<Self> {
+        Self::ALL.get(index).copied()
+    }
+
+    /// in ticks
+    pub fn duration(self) -> u32 {
+        let seconds = match self {
+            PowerUpKind::Invert => 5,
+            PowerUpKind::SpeedUp | PowerUpKind::SlowDown => 8,
+            _ => 10,
+        };
+        seconds * FPS as u32
+    }
+
+    pub fn color(self) -> Color {
+        POWER_UP_COLORS[self.index()]
+    }
+}
+
+/// waiting in the mid court to be touched by a ball
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pickup {
+    pub kind: PowerUpKind,
+    pub rect: Rect,
+}
+
+/// a collected power-up, `paddle` is the one credited for it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Effect {
+    pub kind: PowerUpKind,
+    pub paddle: usize,
+    pub ticks_left: u32,
+    /// tells apart the effects of the same kind
+    pub id: u32,
+}
+
+/// pickups in the court and effects in play, every random choice comes from the seed
+#[derive(Debug, Clone, PartialEq)]
+pub struct PowerUps {
+    enabled: Vec<PowerUpKind>,
+    rng: ChaCha8Rng,
+    pickups: Vec<Pickup>,
+    effects: Vec<Effect>,
+    next_id: u32,
+}
+
+impl PowerUps {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            enabled: vec![],
+            rng: ChaCha8Rng::seed_from_u64(seed),
+            pickups: vec![],
+            effects: vec![],
+            next_id: 0,
+        }
+    }
+
+    pub fn set_enabled(&mut self, enabled: &[PowerUpKind]) {
+        self.enabled = enabled.to_vec();
+    }
+
+    pub fn pickups(&self) -> &[Pickup] {
+        &self.pickups
+    }
+
+    pub fn effects(&self) -> &[Effect] {
+        &self.effects
+    }
+
+    pub fn clear(&mut self) {
+        self.pickups.clear();
+        self.effects.clear();
+    }
+
+    /// a new pickup every `PICKUP_SPAWN` ticks, somewhere in the mid court
+    pub fn spawn(&mut self, tick: u32) {
+        if self.enabled.is_empty()
+            || !tick.is_multiple_of(PICKUP_SPAWN)
+            || self.pickups.len() >= MAX_PICKUPS
+        {
+            return;
+        }
+        let kind = self.enabled[self.rng.gen_range(0..self.enabled.len())];
+        let x = self
+            .rng
+            .gen_range(WINDOW_WIDTH * 3 / 8..WINDOW_WIDTH * 5 / 8 - PICKUP_SIZE);
+        let y = self
+            .rng
+            .gen_range(WINDOW_HEIGHT / 8..WINDOW_HEIGHT * 7 / 8 - PICKUP_SIZE);
+        let rect = Rect::new(x as i32, y as i32, PICKUP_SIZE, PICKUP_SIZE);
+        self.pickups.push(Pickup { kind, rect });
+    }
+
+    /// removes the first pickup touched by `rect`
+    pub fn collect(&mut self, rect: Rect) -> Option<PowerUpKind> {
+        let touched = |pickup: &Pickup| {
+            let x = Segmet1D::new(rect.x(), rect.x() + rect.width() as i32);
+            let y = Segmet1D::new(rect.y(), rect.y() + rect.height() as i32);
+            x.intersect(&Segmet1D::new(pickup.rect.x(), pickup.rect.right()))
+                && y.intersect(&Segmet1D::new(pickup.rect.y(), pickup.rect.bottom()))
+        };
+        let i = self.pickups.iter().position(touched)?;
+        Some(self.pickups.remove(i).kind)
+    }
+
+    /// returns the id of the new effect
+    pub fn add(&mut self, kind: PowerUpKind, paddle: usize) -> u32 {
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        self.effects.push(Effect {
+            kind,
+            paddle,
+            ticks_left: kind.duration(),
+            id,
+        });
+        id
+    }
+
+    /// returns the effects that have just run out
+    pub fn tick(&mut self) -> Vec<Effect> {
+        for effect in &mut self.effects {
+            effect.ticks_left = effect.ticks_left.saturating_sub(1);
+        }
+        let (expired, effects) = self
+            .effects
+            .iter()
+            .partition(|effect| effect.ticks_left == 0);
+        self.effects = effects;
+        expired
+    }
+
+    /// how many times `kind` is active, `credited` tells which effects count
+    pub fn count(&self, kind: PowerUpKind, credited: impl Fn(usize) -> bool) -> i32 {
+        self.effects
+            .iter()
+            .filter(|effect| effect.kind == kind && credited(effect.paddle))
+            .count() as i32
+    }
+
+    pub fn write(&self, writer: &mut PacketWriter) {
+        writer.put_u128(self.rng.get_word_pos());
+        writer.put_u8(self.pickups.len() as u8);
+        for pickup in &self.pickups {
+            writer.put_u8(pickup.kind.index() as u8);
+            writer.put_i32(pickup.rect.x());
+            writer.put_i32(pickup.rect.y());
+        }
+        writer.put_u8(self.effects.len() as u8);
+        for effect in &self.effects {
+            writer.put_u8(effect.kind.index() as u8);
+            writer.put_u8(effect.paddle as u8);
+            writer.put_u32(effect.ticks_left);
+            writer.put_u32(effect.id);
+        }
+        writer.put_u32(self.next_id);
+    }
+
+    /// the effects are on one of the `paddles`
+    pub fn read(&mut self, reader: &mut PacketReader, paddles: usize) -> Result<()> {
+        self.rng.set_word_pos(reader.get_u128()?);
+        self.pickups.clear();
+        for _ in 0..reader.get_u8()? {
+            let kind = read_kind(reader)?;
+            let x = reader.get_i32()?;
+            let rect = Rect::new(x, reader.get_i32()?, PICKUP_SIZE, PICKUP_SIZE);
+            self.pickups.push(Pickup { kind, rect });
+        }
+        self.effects.clear();
+        for _ in 0..reader.get_u8()? {
+            let kind = read_kind(reader)?;
+            let paddle = reader.get_u8()? as usize;
+            if paddle >= paddles {
+                return Err(Box::new(InvalidPacket));
+            }
+            let ticks_left = reader.get_u32()?;
+            let id = reader.get_u32()?;
+            self.effects.push(Effect {
+                kind,
+                paddle,
+                ticks_left,
+                id,
+            });
+        }
+        self.next_id = reader.get_u32()?;
+        Ok(())
+    }
+}
+
+fn read_kind(reader: &mut PacketReader) -> Result<PowerUpKind> {
+    Ok(PowerUpKind::from_index(reader.get_u8()? as usize).ok_or(InvalidPacket)?)
+}
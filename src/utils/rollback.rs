@@ -0,0 +1,192 @@
+use crate::net::{step, NetEvent, NetInput, NetSession};
+use crate::world::World;
+use crate::Result;
+use std::collections::VecDeque;
+
+const MAX_PREDICTION: u32 = 8;
+
+/// the local input is applied at once and the remote one is predicted
+/// (the last one received is repeated); when the real remote input differs
+/// the world is restored from a snapshot and simulated again
+pub struct Rollback {
+    session: NetSession,
+    tick: u32,
+    confirmed: u32,
+    frames: VecDeque<(World, NetInput, NetInput)>,
+    reset_requested: bool,
+    rollbacks: u32,
+}
+
+impl Rollback {
+    pub fn new(session: NetSession) -> Self {
+        Self {
+            session,
+            tick: 0,
+            confirmed: 0,
+            frames: VecDeque::new(),
+            reset_requested: false,
+            rollbacks: 0,
+        }
+    }
+
+    pub fn session(&self) -> &NetSession {
+        &self.session
+    }
+
+    pub fn session_mut(&mut self) -> &mut NetSession {
+        &mut self.session
+    }
+
+    pub fn rollbacks(&self) -> u32 {
+        self.rollbacks
+    }
+
+    pub fn request_reset(&mut self) {
+        self.reset_requested = true;
+    }
+
+    /// returns false when the peer is gone
+    pub fn update(&mut self, world: &mut World, local: NetInput) -> Result<bool> {
+        if self.session.local_len() <= self.tick + self.session.delay() {
+            self.session.push_local(NetInput {
+                reset: local.reset || self.reset_requested,
+                ..local
+            });
+            self.reset_requested = false;
+        }
+        if !self.sync(world)? {
+            return Ok(false);
+        }
+        if self.tick - self.confirmed < MAX_PREDICTION {
+            let (input_l, input_r) = self.inputs(self.tick);
+            self.frames.push_back((world.clone(), input_l, input_r));
+            step(world, input_l, input_r)?;
+            self.tick += 1;
+        }
+        Ok(true)
+    }
+
+    /// exchanges the inputs and fixes the mispredictions, without advancing
+    pub fn sync(&mut self, world: &mut World) -> Result<bool> {
+        for event in self.session.poll()? {
+            if let NetEvent::Disconnected = event {
+                return Ok(false);
+            }
+        }
+        self.session.send_inputs()?;
+
+        let known = self.session.remote_len().min(self.tick);
+        let mispredicted = (self.confirmed..known).find(|tick| {
+            let (input_l, input_r) = self.frame(*tick).1;
+            let used = if self.session.is_host() {
+                input_r
+            } else {
+                input_l
+            };
+            Some(used) != self.session.remote_input(*tick)
+        });
+        if let Some(first) = mispredicted {
+            self.rollbacks += 1;
+            let mut replay = self.frame(first).0.clone();
+            for tick in first..self.tick {
+                let (input_l, input_r) = self.inputs(tick);
+                self.frames[(tick - self.confirmed) as usize] = (replay.clone(), input_l, input_r);
+                step(&mut replay, input_l, input_r)?;
+            }
+            *world = replay;
+        }
+        for _ in self.confirmed..known {
+            self.frames.pop_front();
+        }
+        self.confirmed = self.confirmed.max(known);
+        Ok(true)
+    }
+
+    fn frame(&self, tick: u32) -> (&World, (NetInput, NetInput)) {
+        let (world, input_l, input_r) = &self.frames[(tick - self.confirmed) as usize];
+        (world, (*input_l, *input_r))
+    }
+
+    /// the real inputs when known, otherwise the prediction
+    fn inputs(&self, tick: u32) -> (NetInput, NetInput) {
+        let local = self.session.local_input(tick).unwrap_or_default();
+        let remote = self.session.remote_input(tick).unwrap_or_else(|| {
+            let last = self.session.remote_len().checked_sub(1);
+            NetInput {
+                movement: last
+                    .and_then(|last| self.session.remote_input(last))
+                    .unwrap_or_default()
+                    .movement,
+                reset: false,
+            }
+        });
+        if self.session.is_host() {
+            (local, remote)
+        } else {
+            (remote, local)
+        }
+    }
+}
+
+#[test]
+fn test() {
+    use crate::mode::Mode;
+    use crate::net::NetConfig;
+    use crate::packet::PacketWriter;
+    use crate::paddle::PaddleMove;
+    use std::time::{Duration, Instant};
+
+    let config = NetConfig {
+        delay: 0,
+        latency: Duration::from_millis(20),
+        loss: 0.0,
+        timeout: Duration::from_secs(5),
+    };
+    let socket = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+    let addr = socket.local_addr().unwrap();
+    drop(socket);
+    let host = std::thread::spawn(move || NetSession::host(addr.port(), config).unwrap());
+    std::thread::sleep(Duration::from_millis(50));
+    let client = NetSession::join(&addr.to_string(), config).unwrap();
+    let host = host.join().unwrap();
+    let mut world_h = World::new(Mode::Classic, host.seed());
+    let mut world_c = World::new(Mode::Classic, client.seed());
+    let mut host = Rollback::new(host);
+    let mut client = Rollback::new(client);
+
+    // the moves change every 25 ticks, far less often than the inputs take to
+    // cross, so the repeated one is always mispredicted for a while
+    let moves = [PaddleMove::Up, PaddleMove::Stay, PaddleMove::Down];
+    let script = |side: usize, tick: u32| NetInput {
+        movement: moves[(tick as usize / 25 + side) % 3],
+        reset: side == 0 && tick == 150,
+    };
+    let start = Instant::now();
+    let last_tick = 300;
+    while host.tick < last_tick
+        || client.tick < last_tick
+        || host.confirmed < last_tick
+        || client.confirmed < last_tick
+    {
+        assert!(start.elapsed() < Duration::from_secs(20));
+        for (side, rollback, world) in
+            [(0, &mut host, &mut world_h), (1, &mut client, &mut world_c)]
+        {
+            if rollback.tick < last_tick {
+                let input = script(side, rollback.tick);
+                assert!(rollback.update(world, input).unwrap());
+            } else {
+                assert!(rollback.sync(world).unwrap());
+            }
+        }
+        std::thread::sleep(Duration::from_millis(2));
+    }
+    assert!(host.rollbacks() > 0 && client.rollbacks() > 0);
+    let bytes = |world: &World| {
+        let mut writer = PacketWriter::new(0);
+        world.write(&mut writer);
+        writer.into_bytes()
+    };
+    assert_eq!(bytes(&world_h), bytes(&world_c));
+    assert_eq!(world_h, world_c);
+}
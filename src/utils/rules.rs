@@ -0,0 +1,139 @@
+use crate::constants::*;
+use crate::error::InvalidPacket;
+use crate::packet::{PacketReader, PacketWriter};
+use crate::team::TeamName;
+use crate::Result;
+
+/// who serves the next point of a two teams match
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Serve {
+    /// the team that has just conceded
+    Loser,
+    /// the left team serves the first `n` points of a set, then the right one, and so on
+    Alternate(u32),
+}
+
+impl Serve {
+    /// `loser` or `alternate:N`
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.split_once(':') {
+            None if name == "loser" => Some(Serve::Loser),
+            Some(("alternate", n)) => n.parse().ok().filter(|n| *n > 0).map(Serve::Alternate),
+            _ => None,
+        }
+    }
+}
+
+/// how a two teams match is won, a four player one only counts lives
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rules {
+    pub points_to_win: u32,
+    /// the winner of a set must lead by this many points
+    pub win_by: u32,
+    /// in ticks, when it runs out the leader wins the set and a tie goes to the next point
+    pub time_limit: Option<u32>,
+    /// best of `sets`
+    pub sets: u32,
+    pub serve: Serve,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self {
+            points_to_win: POINT_TO_WIN,
+            win_by: 1,
+            time_limit: None,
+            sets: 1,
+            serve: Serve::Loser,
+        }
+    }
+}
+
+impl Rules {
+    pub fn sets_to_win(&self) -> u32 {
+        self.sets / 2 + 1
+    }
+
+    /// `played` is the number of ticks since the start of the set
+    pub fn set_winner(&self, points: &[(TeamName, u32)], played: u32) -> Option<TeamName> {
+        let (leader, best) = points.iter().copied().max_by_key(|(_, points)| *points)?;
+        let second = points
+            .iter()
+            .filter(|(team, _)| *team != leader)
+            .map(|(_, points)| *points)
+            .max()
+            .unwrap_or(0);
+        let lead = best.saturating_sub(second);
+        let on_points = best >= self.points_to_win && lead >= self.win_by;
+        let on_time = self.time_limit.is_some_and(|limit| played >= limit) && lead > 0;
+        (on_points || on_time).then_some(leader)
+    }
+
+    pub fn write(&self, writer: &mut PacketWriter) {
+        writer.put_u32(self.points_to_win);
+        writer.put_u32(self.win_by);
+        writer.put_u32(self.time_limit.unwrap_or(u32::MAX));
+        writer.put_u32(self.sets);
+        // 0 for the loser, `n` for alternate:n
+        writer.put_u32(match self.serve {
+            Serve::Loser => 0,
+            Serve::Alternate(n) => n,
+        });
+    }
+
+    pub fn read(reader: &mut PacketReader) -> Result<Self> {
+        let points_to_win = reader.get_u32()?;
+        let win_by = reader.get_u32()?;
+        let time_limit = Some(reader.get_u32()?).filter(|limit| *limit != u32::MAX);
+        let sets = reader.get_u32()?;
+        if sets == 0 {
+            return Err(Box::new(InvalidPacket));
+        }
+        let serve = match reader.get_u32()? {
+            0 => Serve::Loser,
+            n => Serve::Alternate(n),
+        };
+        Ok(Self {
+            points_to_win,
+            win_by,
+            time_limit,
+            sets,
+            serve,
+        })
+    }
+
+    /// `played` is the number of points already played in the set
+    pub fn server(&self, loser: TeamName, played: u32) -> TeamName {
+        match self.serve {
+            Serve::Loser => loser,
+            Serve::Alternate(n) if (played / n.max(1)).is_multiple_of(2) => TeamName::Left,
+            Serve::Alternate(_) => TeamName::Right,
+        }
+    }
+}
+
+#[test]
+fn test() {
+    let rules = Rules {
+        points_to_win: 11,
+        win_by: 2,
+        time_limit: Some(100),
+        sets: 3,
+        serve: Serve::from_name("alternate:2").unwrap(),
+    };
+    let score = |left, right| [(TeamName::Left, left), (TeamName::Right, right)];
+    assert_eq!(rules.set_winner(&score(11, 10), 0), None);
+    assert_eq!(rules.set_winner(&score(10, 12), 0), Some(TeamName::Right));
+    assert_eq!(rules.set_winner(&score(3, 2), 100), Some(TeamName::Left));
+    assert_eq!(rules.set_winner(&score(3, 3), 100), None);
+    assert_eq!(rules.sets_to_win(), 2);
+    assert_eq!(rules.server(TeamName::Left, 1), TeamName::Left);
+    assert_eq!(rules.server(TeamName::Left, 2), TeamName::Right);
+    assert_eq!(Serve::from_name("loser"), Some(Serve::Loser));
+    assert_eq!(Serve::from_name("alternate:0"), None);
+
+    let mut writer = PacketWriter::new(0);
+    rules.write(&mut writer);
+    let mut reader = PacketReader::new(&writer.bytes()[1..]);
+    assert_eq!(Rules::read(&mut reader).unwrap(), rules);
+}
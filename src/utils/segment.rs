@@ -17,7 +17,7 @@ impl Segmet1D {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Segmet2D {
     x1: i32,
     y1: i32,
@@ -55,6 +55,18 @@ impl Segmet2D {
             None
         }
     }
+    pub fn start(&self) -> (i32, i32) {
+        (self.x1, self.y1)
+    }
+    pub fn end(&self) -> (i32, i32) {
+        (self.x2, self.y2)
+    }
+    pub fn is_vertical(&self) -> bool {
+        self.x1 == self.x2
+    }
+    pub fn is_horizontal(&self) -> bool {
+        self.y1 == self.y2
+    }
     pub fn change_x1(&mut self, new_x1: i32) {
         self.x1 = new_x1;
     }
@@ -67,6 +79,12 @@ impl Segmet2D {
     pub fn change_y2(&mut self, new_y2: i32) {
         self.y2 = new_y2;
     }
+    pub fn x_min(&self) -> i32 {
+        self.x1.min(self.x2)
+    }
+    pub fn x_max(&self) -> i32 {
+        self.x1.max(self.x2)
+    }
     pub fn y_min(&self) -> i32 {
         if self.y1 < self.y2 {
             self.y1
@@ -0,0 +1,65 @@
+use sdl2::audio::{AudioQueue, AudioSpecDesired};
+use sdl2::Sdl;
+
+use crate::constants::*;
+
+/// short beeps played through a queue, silent without an audio device
+#[derive(Default)]
+pub struct Sound {
+    queue: Option<AudioQueue<i16>>,
+}
+
+impl Sound {
+    pub fn new(sdl_context: &Sdl) -> Self {
+        let spec = AudioSpecDesired {
+            freq: Some(AUDIO_RATE as i32),
+            channels: Some(1),
+            samples: None,
+        };
+        let queue = sdl_context
+            .audio()
+            .and_then(|audio| audio.open_queue::<i16, _>(None, &spec))
+            .ok();
+        if let Some(queue) = &queue {
+            queue.resume();
+        }
+        Self { queue }
+    }
+
+    /// cuts the beep still playing
+    pub fn beep(&self, frequency: u32) {
+        let Some(queue) = &self.queue else {
+            return;
+        };
+        queue.clear();
+        let rate = queue.spec().freq.max(1) as u32;
+        // a beep not played is not worth stopping the game
+        let _ = queue.queue_audio(&tone(frequency, BEEP_MS, rate));
+    }
+}
+
+/// a square wave of `ms` milliseconds fading out, at `rate` samples per second
+pub fn tone(frequency: u32, ms: u32, rate: u32) -> Vec<i16> {
+    let samples = rate * ms / 1000;
+    let period = (rate / frequency.max(1)).max(2);
+    (0..samples)
+        .map(|i| {
+            let volume = BEEP_VOLUME as i32 * (samples - i) as i32 / samples as i32;
+            if i % period < period / 2 {
+                volume as i16
+            } else {
+                -volume as i16
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn test() {
+    let samples = tone(1000, 100, 8000);
+    assert_eq!(samples.len(), 800);
+    assert_eq!(samples[0], BEEP_VOLUME);
+    assert_eq!(samples[4], -samples[4].abs());
+    assert!(samples[8] > 0 && samples[8] < BEEP_VOLUME);
+    assert!(samples.iter().all(|sample| sample.abs() <= BEEP_VOLUME));
+}
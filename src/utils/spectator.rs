@@ -0,0 +1,530 @@
+use crate::constants::*;
+use crate::decorations::Decoration;
+use crate::error::{InvalidPacket, PacketTooShort};
+use crate::game_status::GameStatus;
+use crate::mode::Mode;
+use crate::packet::{PacketReader, PacketWriter};
+use crate::powerup::{Pickup, PowerUpKind};
+use crate::rules::Rules;
+use crate::segment::Segmet2D;
+use crate::team::TeamName;
+use crate::world::World;
+use crate::Result;
+use sdl2::rect::Rect;
+use std::collections::VecDeque;
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::{Duration, Instant};
+
+const SNAPSHOT: u8 = 0;
+const FRAME: u8 = 1;
+const SCORE: u8 = 2;
+const STATUS: u8 = 3;
+const MATCH: u8 = 4;
+
+/// the most of each kind of thing in a frame, their count is a u16
+const MAX_ITEMS: usize = u16::MAX as usize;
+/// longer than a snapshot with every list full, anything longer is not from a host
+const MAX_MESSAGE: usize = (MAX_ITEMS + 1) * 128;
+
+/// what a spectator needs to draw a frame
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    /// only the paddles still in play
+    pub paddles: Vec<Rect>,
+    /// the team of each paddle
+    pub teams: Vec<TeamName>,
+    pub balls: Vec<Rect>,
+    pub pickups: Vec<Pickup>,
+    /// the obstacles of the arena, the posts beside the goal mouths and the bricks
+    pub obstacles: Vec<Rect>,
+    pub walls: Vec<Segmet2D>,
+    /// the decorations of the arena
+    pub decorations: Vec<Decoration>,
+    /// indexed by `TeamName::index`
+    pub points: [u32; 4],
+    pub sets: [u32; 4],
+    pub status: GameStatus,
+    /// what the counters of the point display are
+    pub mode: Mode,
+    pub rules: Rules,
+}
+
+impl Snapshot {
+    pub fn new(world: &World, status: GameStatus) -> Self {
+        Self {
+            paddles: world.active_paddles().map(|paddle| paddle.rect()).collect(),
+            teams: world.active_paddles().map(|paddle| paddle.team()).collect(),
+            balls: world.balls().iter().map(|ball| ball.rect()).collect(),
+            pickups: world
+                .power_ups()
+                .pickups()
+                .iter()
+                .take(MAX_ITEMS)
+                .cloned()
+                .collect(),
+            obstacles: world
+                .arena()
+                .obstacles(world.tick())
+                .into_iter()
+                .chain(world.arena().posts(world.mode().teams()))
+                .chain(world.bricks().alive().map(|(_, rect, _)| rect))
+                .take(MAX_ITEMS)
+                .collect(),
+            walls: world
+                .arena()
+                .walls()
+                .iter()
+                .take(MAX_ITEMS)
+                .cloned()
+                .collect(),
+            decorations: world
+                .arena()
+                .decorations()
+                .iter()
+                .take(MAX_ITEMS)
+                .copied()
+                .collect(),
+            points: TeamName::ALL.map(|team| world.points(team)),
+            sets: TeamName::ALL.map(|team| world.sets(team)),
+            status,
+            mode: world.mode(),
+            rules: *world.rules(),
+        }
+    }
+
+    fn write_frame(&self, writer: &mut PacketWriter) {
+        for rects in [&self.paddles, &self.balls, &self.obstacles] {
+            writer.put_u16(rects.len() as u16);
+            for rect in rects {
+                writer.put_i32(rect.x());
+                writer.put_i32(rect.y());
+                writer.put_u32(rect.width());
+                writer.put_u32(rect.height());
+            }
+        }
+        for team in &self.teams {
+            writer.put_u8(team.index() as u8);
+        }
+        writer.put_u16(self.pickups.len() as u16);
+        for pickup in &self.pickups {
+            writer.put_u8(pickup.kind.index() as u8);
+            writer.put_i32(pickup.rect.x());
+            writer.put_i32(pickup.rect.y());
+        }
+        writer.put_u16(self.walls.len() as u16);
+        for wall in &self.walls {
+            for (x, y) in [wall.start(), wall.end()] {
+                writer.put_i32(x);
+                writer.put_i32(y);
+            }
+        }
+        writer.put_u16(self.decorations.len() as u16);
+        for decoration in &self.decorations {
+            decoration.write(writer);
+        }
+    }
+
+    fn write_score(&self, writer: &mut PacketWriter) {
+        for points in self.points.iter().chain(&self.sets) {
+            writer.put_u32(*points);
+        }
+    }
+
+    fn write_status(&self, writer: &mut PacketWriter) {
+        writer.put_u8(status_to_byte(self.status));
+    }
+
+    fn write_match(&self, writer: &mut PacketWriter) {
+        writer.put_u8(self.mode.index() as u8);
+        self.rules.write(writer);
+    }
+
+    fn read_frame(&mut self, reader: &mut PacketReader) -> Result<()> {
+        for rects in [&mut self.paddles, &mut self.balls, &mut self.obstacles] {
+            let len = reader.get_u16()? as usize;
+            rects.clear();
+            for _ in 0..len {
+                rects.push(read_rect(reader)?);
+            }
+        }
+        self.teams.clear();
+        for _ in 0..self.paddles.len() {
+            let team = TeamName::ALL.get(reader.get_u8()? as usize);
+            self.teams.push(*team.ok_or(InvalidPacket)?);
+        }
+        self.pickups.clear();
+        for _ in 0..reader.get_u16()? {
+            let kind = PowerUpKind::from_index(reader.get_u8()? as usize).ok_or(InvalidPacket)?;
+            let x = reader.get_i32()?;
+            let rect = Rect::new(x, reader.get_i32()?, PICKUP_SIZE, PICKUP_SIZE);
+            self.pickups.push(Pickup { kind, rect });
+        }
+        self.walls.clear();
+        for _ in 0..reader.get_u16()? {
+            let x1 = reader.get_i32()?;
+            let y1 = reader.get_i32()?;
+            let x2 = reader.get_i32()?;
+            let y2 = reader.get_i32()?;
+            self.walls.push(Segmet2D::new(x1, y1, x2, y2));
+        }
+        self.decorations.clear();
+        for _ in 0..reader.get_u16()? {
+            self.decorations.push(Decoration::read(reader)?);
+        }
+        Ok(())
+    }
+
+    fn read_score(&mut self, reader: &mut PacketReader) -> Result<()> {
+        for points in self.points.iter_mut().chain(&mut self.sets) {
+            *points = reader.get_u32()?;
+        }
+        Ok(())
+    }
+
+    fn read_status(&mut self, reader: &mut PacketReader) -> Result<()> {
+        self.status = status_from_byte(reader.get_u8()?);
+        Ok(())
+    }
+
+    fn read_match(&mut self, reader: &mut PacketReader) -> Result<()> {
+        let mode = Mode::ALL.get(reader.get_u8()? as usize);
+        self.mode = *mode.ok_or(InvalidPacket)?;
+        self.rules = Rules::read(reader)?;
+        Ok(())
+    }
+}
+
+fn read_rect(reader: &mut PacketReader) -> Result<Rect> {
+    let x = reader.get_i32()?;
+    let y = reader.get_i32()?;
+    Ok(Rect::new(x, y, reader.get_u32()?, reader.get_u32()?))
+}
+
+/// every message is sent as `[length: u32][packet]`
+fn message(writer: PacketWriter) -> Vec<u8> {
+    let packet = writer.into_bytes();
+    let mut message = (packet.len() as u32).to_le_bytes().to_vec();
+    message.extend(packet);
+    message
+}
+
+/// a spectator and the bytes it has not received yet
+struct Spectator {
+    stream: TcpStream,
+    pending: Vec<u8>,
+}
+
+impl Spectator {
+    /// sends what the socket takes without waiting, false once the spectator is
+    /// gone or too far behind
+    fn send(&mut self, message: &[u8]) -> bool {
+        self.pending.extend_from_slice(message);
+        if self.pending.len() > SPECTATOR_BACKLOG {
+            return false;
+        }
+        while !self.pending.is_empty() {
+            match self.stream.write(&self.pending) {
+                Ok(0) => return false,
+                Ok(len) => {
+                    self.pending.drain(..len);
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(_) => return false,
+            }
+        }
+        true
+    }
+}
+
+/// late joiners receive a full snapshot with the mode and the rules of the match,
+/// then a frame for each tick and the score and the status when they change; the
+/// host never waits for a spectator
+pub struct SpectatorServer {
+    listener: TcpListener,
+    clients: Vec<Spectator>,
+    last: Option<Snapshot>,
+}
+
+impl SpectatorServer {
+    pub fn bind(port: u16) -> Result<Self> {
+        let listener = TcpListener::bind(("0.0.0.0", port))?;
+        listener.set_nonblocking(true)?;
+        Ok(Self {
+            listener,
+            clients: vec![],
+            last: None,
+        })
+    }
+
+    pub fn spectators(&self) -> usize {
+        self.clients.len()
+    }
+
+    pub fn broadcast(&mut self, snapshot: &Snapshot) -> Result<()> {
+        let mut frame = PacketWriter::new(FRAME);
+        snapshot.write_frame(&mut frame);
+        let mut update = message(frame);
+        if self.last.as_ref().map(|last| last.points) != Some(snapshot.points) {
+            let mut score = PacketWriter::new(SCORE);
+            snapshot.write_score(&mut score);
+            update.extend(message(score));
+        }
+        if self.last.as_ref().map(|last| last.status) != Some(snapshot.status) {
+            let mut status = PacketWriter::new(STATUS);
+            snapshot.write_status(&mut status);
+            update.extend(message(status));
+        }
+        if self.last.as_ref().map(|last| (last.mode, last.rules))
+            != Some((snapshot.mode, snapshot.rules))
+        {
+            let mut rules = PacketWriter::new(MATCH);
+            snapshot.write_match(&mut rules);
+            update.extend(message(rules));
+        }
+        self.last = Some(snapshot.clone());
+        self.clients.retain_mut(|client| client.send(&update));
+
+        loop {
+            let stream = match self.listener.accept() {
+                Ok((stream, _)) => stream,
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => return Err(Box::new(e)),
+            };
+            let mut full = PacketWriter::new(SNAPSHOT);
+            snapshot.write_match(&mut full);
+            snapshot.write_frame(&mut full);
+            snapshot.write_score(&mut full);
+            snapshot.write_status(&mut full);
+            stream.set_nonblocking(true)?;
+            stream.set_nodelay(true)?;
+            let mut client = Spectator {
+                stream,
+                pending: vec![],
+            };
+            if client.send(&message(full)) {
+                self.clients.push(client);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// shows what the host was showing `delay` ago
+pub struct SpectatorClient {
+    stream: TcpStream,
+    buf: Vec<u8>,
+    current: Option<Snapshot>,
+    received: VecDeque<(Instant, Snapshot)>,
+    shown: Option<Snapshot>,
+    delay: Duration,
+    connected: bool,
+}
+
+impl SpectatorClient {
+    pub fn connect(addr: &str, delay: Duration) -> Result<Self> {
+        let stream = TcpStream::connect(addr)?;
+        stream.set_nonblocking(true)?;
+        Ok(Self {
+            stream,
+            buf: vec![],
+            current: None,
+            received: VecDeque::new(),
+            shown: None,
+            delay,
+            connected: true,
+        })
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connected
+    }
+
+    pub fn poll(&mut self) -> Result<()> {
+        let mut chunk = [0; 4096];
+        while self.connected {
+            match self.stream.read(&mut chunk) {
+                Ok(0) => self.connected = false,
+                Ok(len) => self.buf.extend_from_slice(&chunk[..len]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(_) => self.connected = false,
+            }
+        }
+        while self.buf.len() >= 4 {
+            let len = u32::from_le_bytes(self.buf[..4].try_into()?) as usize;
+            if len > MAX_MESSAGE {
+                self.connected = false;
+                self.buf.clear();
+                break;
+            }
+            if self.buf.len() < 4 + len {
+                break;
+            }
+            let packet: Vec<u8> = self.buf.drain(..4 + len).skip(4).collect();
+            // a packet that can't be read is skipped, the next frame replaces it
+            let _ = self.apply(&packet);
+        }
+        Ok(())
+    }
+
+    fn apply(&mut self, packet: &[u8]) -> Result<()> {
+        let (kind, rest) = packet.split_first().ok_or(PacketTooShort)?;
+        let mut reader = PacketReader::new(rest);
+        let mut snapshot = match (*kind, &self.current) {
+            (SNAPSHOT, _) => Snapshot {
+                paddles: vec![],
+                teams: vec![],
+                balls: vec![],
+                pickups: vec![],
+                obstacles: vec![],
+                walls: vec![],
+                decorations: vec![],
+                points: [0; 4],
+                sets: [0; 4],
+                status: GameStatus::Neutral,
+                mode: Mode::Classic,
+                rules: Rules::default(),
+            },
+            (_, Some(current)) => current.clone(),
+            (_, None) => return Ok(()),
+        };
+        match *kind {
+            SNAPSHOT => {
+                snapshot.read_match(&mut reader)?;
+                snapshot.read_frame(&mut reader)?;
+                snapshot.read_score(&mut reader)?;
+                snapshot.read_status(&mut reader)?;
+            }
+            FRAME => snapshot.read_frame(&mut reader)?,
+            SCORE => snapshot.read_score(&mut reader)?,
+            STATUS => snapshot.read_status(&mut reader)?,
+            MATCH => snapshot.read_match(&mut reader)?,
+            _ => return Ok(()),
+        }
+        self.current = Some(snapshot.clone());
+        if *kind == FRAME || *kind == SNAPSHOT {
+            self.received.push_back((Instant::now(), snapshot));
+        } else if let Some((_, last)) = self.received.back_mut() {
+            *last = snapshot;
+        }
+        Ok(())
+    }
+
+    /// the newest snapshot that is at least `delay` old
+    pub fn view(&mut self) -> Option<Snapshot> {
+        while let Some((time, snapshot)) = self.received.front() {
+            if time.elapsed() < self.delay {
+                break;
+            }
+            self.shown = Some(snapshot.clone());
+            self.received.pop_front();
+        }
+        self.shown.clone()
+    }
+}
+
+fn status_to_byte(status: GameStatus) -> u8 {
+    match status {
+        GameStatus::Neutral => 0,
+        GameStatus::Play => 1,
+        GameStatus::Quit => 2,
+        GameStatus::Waiting => 3,
+        GameStatus::Reset => 4,
+    }
+}
+
+fn status_from_byte(byte: u8) -> GameStatus {
+    match byte {
+        1 => GameStatus::Play,
+        2 => GameStatus::Quit,
+        3 => GameStatus::Waiting,
+        4 => GameStatus::Reset,
+        _ => GameStatus::Neutral,
+    }
+}
+
+#[test]
+fn test() {
+    use crate::mode::Mode;
+    use crate::paddle::PaddleMove;
+
+    let mut server = SpectatorServer::bind(0).unwrap();
+    let addr = format!("127.0.0.1:{}", server.listener.local_addr().unwrap().port());
+    let mut world = World::new(Mode::FourPlayer, 3);
+    let mut snapshot = Snapshot::new(&world, GameStatus::Play);
+    server.broadcast(&snapshot).unwrap();
+
+    let mut early = SpectatorClient::connect(&addr, Duration::ZERO).unwrap();
+    let receive = |client: &mut SpectatorClient, expected: &Snapshot| {
+        let start = Instant::now();
+        while client.view().as_ref() != Some(expected) {
+            assert!(start.elapsed() < Duration::from_secs(5));
+            client.poll().unwrap();
+        }
+    };
+    let moves = [
+        PaddleMove::Up,
+        PaddleMove::Down,
+        PaddleMove::Up,
+        PaddleMove::Stay,
+    ];
+    for _ in 0..50 {
+        world.step(&moves).unwrap();
+        snapshot = Snapshot::new(&world, GameStatus::Play);
+        server.broadcast(&snapshot).unwrap();
+    }
+    receive(&mut early, &snapshot);
+    assert_eq!(server.spectators(), 1);
+
+    let mut late = SpectatorClient::connect(&addr, Duration::ZERO).unwrap();
+    world.reset();
+    snapshot = Snapshot::new(&world, GameStatus::Waiting);
+    while server.spectators() < 2 {
+        server.broadcast(&snapshot).unwrap();
+    }
+    receive(&mut late, &snapshot);
+    receive(&mut early, &snapshot);
+    // more obstacles than a byte counts, like a big brick layout
+    snapshot.obstacles = vec![Rect::new(0, 0, 1, 1); 300];
+    let mut writer = PacketWriter::new(FRAME);
+    snapshot.write_frame(&mut writer);
+    let mut copy = snapshot.clone();
+    copy.obstacles.clear();
+    copy.read_frame(&mut PacketReader::new(&writer.bytes()[1..]))
+        .unwrap();
+    assert_eq!(copy, snapshot);
+
+    // the spectators that don't read are dropped instead of slowing the host,
+    // the one that keeps up stays
+    let _stalled = TcpStream::connect(&addr).unwrap();
+    while server.spectators() < 3 {
+        server.broadcast(&snapshot).unwrap();
+    }
+    snapshot.obstacles = vec![Rect::new(0, 0, 1, 1); 3000];
+    let start = Instant::now();
+    while server.spectators() > 1 {
+        assert!(start.elapsed() < Duration::from_secs(10));
+        server.broadcast(&snapshot).unwrap();
+        early.poll().unwrap();
+    }
+    assert!(early.is_connected());
+    receive(&mut early, &snapshot);
+
+    // an unreadable packet is skipped, a message too long to be a snapshot ends
+    // the connection
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut client =
+        SpectatorClient::connect(&listener.local_addr().unwrap().to_string(), Duration::ZERO)
+            .unwrap();
+    let (mut host, _) = listener.accept().unwrap();
+    host.write_all(&[1, 0, 0, 0, SNAPSHOT]).unwrap();
+    host.write_all(&(MAX_MESSAGE as u32 + 1).to_le_bytes())
+        .unwrap();
+    let start = Instant::now();
+    while client.is_connected() {
+        assert!(start.elapsed() < Duration::from_secs(5));
+        client.poll().unwrap();
+    }
+}
@@ -2,4 +2,34 @@
 pub enum TeamName {
     Left,
     Right,
+    Top,
+    Bottom,
+}
+
+impl TeamName {
+    pub const ALL: [TeamName; 4] = [
+        TeamName::Left,
+        TeamName::Right,
+        TeamName::Top,
+        TeamName::Bottom,
+    ];
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    /// the team defending the opposite wall
+    pub fn opponent(self) -> Self {
+        match self {
+            TeamName::Left => TeamName::Right,
+            TeamName::Right => TeamName::Left,
+            TeamName::Top => TeamName::Bottom,
+            TeamName::Bottom => TeamName::Top,
+        }
+    }
+
+    /// top and bottom teams defend their wall with a horizontal paddle
+    pub fn is_horizontal(self) -> bool {
+        matches!(self, TeamName::Top | TeamName::Bottom)
+    }
 }
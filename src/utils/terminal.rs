@@ -0,0 +1,430 @@
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use std::fmt::Write as _;
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use crate::ai::Ai;
+use crate::constants::*;
+use crate::error::InvalidArgument;
+use crate::game::{key_move, paddle_keys, serve_key};
+use crate::point_display::{anchor, Align, GLYPHS, X_PIXEL, Y_PIXEL};
+use crate::segment::Segmet2D;
+use crate::theme::Theme;
+use crate::world::World;
+use crate::Result;
+
+/// the window scaled down to the cells of a terminal, each cell shows two pixels
+/// one above the other with `▀`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid {
+    columns: usize,
+    rows: usize,
+    /// `rows * 2` lines of `columns` pixels
+    pixels: Vec<Color>,
+}
+
+impl Grid {
+    pub fn new(columns: usize, rows: usize, background_color: Color) -> Self {
+        Self {
+            columns,
+            rows,
+            pixels: vec![background_color; columns * rows * 2],
+        }
+    }
+
+    /// the pixel of the grid at `(x, y)` in window coordinates
+    fn pixel(&self, x: i32, y: i32) -> (i32, i32) {
+        (
+            x * self.columns as i32 / WINDOW_WIDTH as i32,
+            y * self.rows as i32 * 2 / WINDOW_HEIGHT as i32,
+        )
+    }
+
+    fn set(&mut self, x: i32, y: i32, color: Color) {
+        if (0..self.columns as i32).contains(&x) && (0..self.rows as i32 * 2).contains(&y) {
+            self.pixels[y as usize * self.columns + x as usize] = color;
+        }
+    }
+
+    /// in window coordinates, anything smaller than a pixel of the grid still gets one
+    pub fn fill_rect(&mut self, rect: Rect, color: Color) {
+        let (left, top) = self.pixel(rect.x(), rect.y());
+        let (right, bottom) = self.pixel(rect.right() - 1, rect.bottom() - 1);
+        for y in top..=bottom.max(top) {
+            for x in left..=right.max(left) {
+                self.set(x, y, color);
+            }
+        }
+    }
+
+    pub fn draw_line(&mut self, segment: &Segmet2D, color: Color) {
+        let (x1, y1) = segment.start();
+        let (x2, y2) = segment.end();
+        let ((left, top), (right, bottom)) = (self.pixel(x1, y1), self.pixel(x2, y2));
+        let steps = (right - left).abs().max((bottom - top).abs()).max(1);
+        for i in 0..=steps {
+            let x = left + (right - left) * i / steps;
+            let y = top + (bottom - top) * i / steps;
+            self.set(x, y, color);
+        }
+    }
+
+    /// with `GLYPHS`, one pixel of the grid for each pixel of a digit;
+    /// `x` and `y` in window coordinates
+    pub fn draw_number(&mut self, number: u32, (x, y, align): (i32, i32, Align), color: Color) {
+        let digits = number.to_string();
+        let advance = X_PIXEL as i32 + 1;
+        let (x, y) = self.pixel(x, y);
+        let x = match align {
+            Align::Left => x,
+            Align::Right => x - digits.len() as i32 * advance,
+        };
+        for (i, digit) in digits.chars().enumerate() {
+            let Some((_, mut bits)) = GLYPHS.iter().copied().find(|(c, _)| *c == digit) else {
+                continue;
+            };
+            for row in 0..Y_PIXEL as i32 {
+                for column in 0..X_PIXEL as i32 {
+                    if bits & 1 == 1 {
+                        self.set(x + i as i32 * advance + column, y + row, color);
+                    }
+                    bits >>= 1;
+                }
+            }
+        }
+    }
+
+    /// ANSI escape codes drawing the grid from the top left corner of the terminal
+    pub fn render(&self) -> String {
+        let mut text = String::from("\x1b[H");
+        let mut current = None;
+        for row in 0..self.rows {
+            if row > 0 {
+                text.push_str("\r\n");
+            }
+            for column in 0..self.columns {
+                let top = self.pixels[row * 2 * self.columns + column];
+                let bottom = self.pixels[(row * 2 + 1) * self.columns + column];
+                if current != Some((top, bottom)) {
+                    let _ = write!(
+                        text,
+                        "\x1b[38;2;{};{};{};48;2;{};{};{}m",
+                        top.r, top.g, top.b, bottom.r, bottom.g, bottom.b
+                    );
+                    current = Some((top, bottom));
+                }
+                text.push('▀');
+            }
+        }
+        text.push_str("\x1b[0m");
+        text
+    }
+}
+
+/// the keys in the bytes read from a terminal in raw mode, the arrows come as
+/// escape sequences and `Ctrl+C` quits
+pub fn parse_keys(bytes: &[u8]) -> Vec<Keycode> {
+    let mut keys = vec![];
+    let mut rest = bytes;
+    while let Some(byte) = rest.first() {
+        match rest {
+            [0x1b, b'[', code, ..] => {
+                keys.extend(match code {
+                    b'A' => Some(Keycode::Up),
+                    b'B' => Some(Keycode::Down),
+                    b'C' => Some(Keycode::Right),
+                    b'D' => Some(Keycode::Left),
+                    _ => None,
+                });
+                rest = &rest[3..];
+                continue;
+            }
+            [0x1b, ..] | [3, ..] => keys.push(Keycode::Escape),
+            _ => keys.extend(Keycode::from_i32(byte.to_ascii_lowercase() as i32)),
+        }
+        rest = &rest[1..];
+    }
+    keys
+}
+
+/// the terminal settings before the game, restored when dropped
+struct RawMode {
+    saved: String,
+}
+
+impl RawMode {
+    fn enable() -> Result<Self> {
+        let saved = stty(&["-g"])?.trim().to_string();
+        stty(&["raw", "-echo"])?;
+        // the alternate screen, without the cursor
+        print!("\x1b[?1049h\x1b[?25l");
+        std::io::stdout().flush()?;
+        Ok(Self { saved })
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        print!("\x1b[?25h\x1b[?1049l");
+        let _ = std::io::stdout().flush();
+        let _ = stty(&[&self.saved]);
+    }
+}
+
+/// runs `stty` on the terminal of the game
+fn stty(args: &[&str]) -> Result<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .output()?;
+    if !output.status.success() {
+        return Err(Box::new(InvalidArgument(format!(
+            "stty {}",
+            args.join(" ")
+        ))));
+    }
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+/// columns and rows, 80×24 if the size is unknown
+fn terminal_size() -> (usize, usize) {
+    let size = stty(&["size"]).ok().and_then(|size| {
+        let (rows, columns) = size.trim().split_once(' ')?;
+        Some((columns.parse().ok()?, rows.parse().ok()?))
+    });
+    size.unwrap_or((80, 24))
+}
+
+/// the bytes typed on the terminal, read by another thread
+fn read_input() -> Receiver<u8> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut buffer = [0; 64];
+        let mut stdin = std::io::stdin();
+        while let Ok(read) = stdin.read(&mut buffer) {
+            if read == 0
+                || buffer[..read]
+                    .iter()
+                    .any(|byte| sender.send(*byte).is_err())
+            {
+                return;
+            }
+        }
+    });
+    receiver
+}
+
+/// plays in a terminal with ANSI escape codes: the terminal only tells when a key
+/// is pressed, so it counts as held for `TERMINAL_KEY_HOLD` ticks
+pub struct Terminal {
+    world: World,
+    ais: Vec<Ai>,
+    cpu: Vec<bool>,
+    /// the keys held and the ticks left before they are released
+    held: Vec<(Keycode, u32)>,
+    paused: bool,
+    fps: u64,
+    theme: Theme,
+}
+
+impl Terminal {
+    pub fn new(world: World, fps: u64) -> Self {
+        let ais = Ai::for_paddles(&world);
+        let cpu = vec![false; world.paddles().len()];
+        Self {
+            world,
+            ais,
+            cpu,
+            held: vec![],
+            paused: false,
+            fps,
+            theme: Theme::default(),
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    /// `paddles` are numbered from 1, in the order of `World::paddles`
+    pub fn set_cpu(&mut self, paddles: &[usize]) -> Result<()> {
+        for paddle in paddles {
+            let cpu = paddle
+                .checked_sub(1)
+                .and_then(|i| self.cpu.get_mut(i))
+                .ok_or_else(|| InvalidArgument(format!("--cpu {paddle}")))?;
+            *cpu = true;
+        }
+        Ok(())
+    }
+
+    pub fn spawn(&mut self) -> Result<()> {
+        let _raw_mode = RawMode::enable()?;
+        let input = read_input();
+        let frame_duration = Duration::from_nanos(1_000_000_000u64 / self.fps);
+        let mut size = terminal_size();
+        for frame in 1u32.. {
+            let loop_start_time = SystemTime::now();
+            let bytes: Vec<_> = input.try_iter().collect();
+            let pressed = parse_keys(&bytes);
+            if pressed.contains(&QUIT) {
+                return Ok(());
+            }
+            self.update(&pressed)?;
+            if frame.is_multiple_of(self.fps as u32) {
+                size = terminal_size();
+            }
+            if frame.is_multiple_of(TERMINAL_FRAME_SKIP) {
+                let mut stdout = std::io::stdout().lock();
+                stdout.write_all(self.draw(size.0, size.1).render().as_bytes())?;
+                stdout.flush()?;
+            }
+            let elapsed_time = SystemTime::now().duration_since(loop_start_time)?;
+            if frame_duration > elapsed_time {
+                thread::sleep(frame_duration - elapsed_time);
+            }
+        }
+        Ok(())
+    }
+
+    /// `pressed` are the keys pressed since the last tick
+    fn update(&mut self, pressed: &[Keycode]) -> Result<()> {
+        for (_, ticks) in &mut self.held {
+            *ticks -= 1;
+        }
+        self.held
+            .retain(|(key, ticks)| *ticks > 0 && !pressed.contains(key));
+        self.held
+            .extend(pressed.iter().map(|key| (*key, TERMINAL_KEY_HOLD)));
+        let keys: Vec<_> = self.held.iter().map(|(key, _)| *key).collect();
+
+        if pressed.contains(&RESET) || (self.world.winner().is_some() && !pressed.is_empty()) {
+            self.world.reset_points();
+            self.paused = false;
+        } else if pressed.contains(&PAUSE) {
+            self.paused = !self.paused;
+        }
+        if self.paused || self.world.winner().is_some() {
+            // the world does not move, the ticks do for the drawing
+            return Ok(());
+        }
+
+        let mut moves = vec![];
+        for (i, paddle) in self.world.paddles().iter().enumerate() {
+            let movement = if self.cpu[i] {
+                self.ais[i].paddle_move(&self.world, i)
+            } else {
+                let row = self.world.paddles()[..i]
+                    .iter()
+                    .filter(|other| other.team() == paddle.team())
+                    .count();
+                let (up, down) = paddle_keys(paddle.team(), row);
+                key_move(&keys, up, down)
+            };
+            moves.push(movement);
+        }
+        for team in self.world.mode().teams() {
+            let Some(i) = self
+                .world
+                .paddles()
+                .iter()
+                .position(|paddle| paddle.team() == *team)
+            else {
+                continue;
+            };
+            let pressed = self.cpu[i] || serve_key(*team).is_some_and(|key| keys.contains(&key));
+            if pressed && self.world.is_serving(*team) {
+                self.world.serve(*team);
+            }
+        }
+        self.world.step(&moves)?;
+        Ok(())
+    }
+
+    fn draw(&self, columns: usize, rows: usize) -> Grid {
+        let theme = &self.theme;
+        let mut grid = Grid::new(columns, rows, theme.background);
+        let decorations = theme.decorations.with(self.world.arena().decorations());
+        let goals = self.world.court().goals;
+        let sides: Vec<_> = goals.iter().map(|(team, ..)| *team).collect();
+        for rect in decorations.zones(&sides) {
+            grid.fill_rect(rect, theme.zone);
+        }
+        for rect in decorations.lines(&sides) {
+            grid.fill_rect(rect, theme.mid_line);
+        }
+        let teams = self.world.mode().counters();
+        for team in teams {
+            let points = self.world.points(*team);
+            grid.draw_number(points, anchor(*team, teams.len() > 2), theme.display);
+        }
+        let seconds = self.world.countdown_left().div_ceil(FPS as u32);
+        if seconds > 0 {
+            let centre = (
+                (WINDOW_WIDTH / 2) as i32,
+                (WINDOW_HEIGHT / 2) as i32,
+                Align::Left,
+            );
+            grid.draw_number(seconds, centre, theme.display);
+        }
+        let arena = self.world.arena();
+        let tick = self.world.tick();
+        let posts = arena.posts(self.world.mode().teams());
+        for rect in arena.obstacles(tick).into_iter().chain(posts) {
+            grid.fill_rect(rect, theme.obstacle);
+        }
+        for wall in arena.walls() {
+            grid.draw_line(wall, theme.obstacle);
+        }
+        for (_, rect, hits) in self.world.bricks().alive() {
+            grid.fill_rect(rect, BRICK_COLORS[hits.clamp(1, 3) as usize - 1]);
+        }
+        for pickup in self.world.power_ups().pickups() {
+            grid.fill_rect(pickup.rect, pickup.kind.color());
+        }
+        let paused = self.paused || self.world.winner().is_some();
+        for paddle in self.world.active_paddles() {
+            grid.fill_rect(paddle.rect(), theme.paddle(paddle.team(), paused));
+        }
+        for ball in self.world.balls() {
+            grid.fill_rect(ball.rect(), theme.ball(paused));
+        }
+        grid
+    }
+}
+
+#[test]
+fn test() {
+    let mut grid = Grid::new(4, 1, BACKGROUND_COLOR);
+    let (width, height) = (WINDOW_WIDTH as i32, WINDOW_HEIGHT as i32);
+    grid.fill_rect(Rect::new(width / 2, height / 2, 1, 1), BALL_COLOR);
+    let text = grid.render();
+    assert_eq!(text.matches('▀').count(), 4);
+    // black on black, then the ball at the bottom of the third cell
+    assert_eq!(text.matches("\x1b[38;2;").count(), 3);
+    assert!(text.contains("38;2;0;0;0;48;2;255;255;0m"));
+    let mut grid = Grid::new(
+        WINDOW_WIDTH as usize,
+        WINDOW_HEIGHT as usize / 2,
+        BACKGROUND_COLOR,
+    );
+    grid.draw_number(10, (0, 0, Align::Left), DISPLAY_COLOR);
+    assert_eq!(
+        grid.pixels.iter().filter(|c| **c == DISPLAY_COLOR).count(),
+        10 + 19
+    );
+
+    assert_eq!(
+        parse_keys(b"w\x1b[AK\x03"),
+        [Keycode::W, Keycode::Up, Keycode::K, Keycode::Escape]
+    );
+    assert_eq!(
+        crate::paddle::PaddleMove::Up,
+        key_move(&parse_keys(b"w"), PADDLE_L_UP, PADDLE_L_DOWN)
+    );
+}
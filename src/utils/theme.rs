@@ -0,0 +1,174 @@
+use crate::constants::*;
+use crate::decorations::{Decoration, Decorations};
+use crate::error::InvalidTheme;
+use crate::team::TeamName;
+use crate::Result;
+use sdl2::pixels::Color;
+
+// the colors not given by a theme are the classic ones
+const BUILTIN: [(&str, &str); 5] = [
+    ("classic", ""),
+    (
+        "amber",
+        "background 16 8 0\npaddle 255 176 0\nball 255 204 64\nmid-line 255 176 0\n\
+         display 204 140 0\nobstacle 153 105 0\n",
+    ),
+    (
+        "green",
+        "background 0 12 0\npaddle 51 255 51\nball 153 255 153\nmid-line 51 255 51\n\
+         display 40 204 40\nobstacle 20 128 20\n",
+    ),
+    (
+        "high-contrast",
+        "paddle 255 255 255\nball 255 255 255\ndisplay 255 255 255\nobstacle 255 255 0\n",
+    ),
+    // the Okabe-Ito palette, told apart with any color vision
+    (
+        "colorblind",
+        "paddle-left 230 159 0\npaddle-right 86 180 233\npaddle-top 0 158 115\n\
+         paddle-bottom 213 94 0\nball 240 228 66\nobstacle 0 114 178\n",
+    ),
+];
+
+/// the colors of the game, the paused ones are derived from them, and the
+/// decorations of the court; theme files have one line for each:
+/// ```text
+/// # comments start with #
+/// background|ball|mid-line|display|obstacle|zone R G B
+/// paddle R G B                          # every team
+/// paddle-left|right|top|bottom R G B
+/// center-line|horizontal-line|boundary solid|dashed|dotted|none
+/// circle|goal-zone SIZE                 # in thousandths of the court
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    pub background: Color,
+    /// indexed by `TeamName::index`
+    pub paddles: [Color; 4],
+    pub ball: Color,
+    pub mid_line: Color,
+    pub display: Color,
+    pub obstacle: Color,
+    /// the goal zones of the decorations
+    pub zone: Color,
+    pub decorations: Decorations,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            background: BACKGROUND_COLOR,
+            paddles: [PADDLE_COLOR; 4],
+            ball: BALL_COLOR,
+            mid_line: MID_LINE_COLOR,
+            display: DISPLAY_COLOR,
+            obstacle: OBSTACLE_COLOR,
+            zone: ZONE_COLOR,
+            decorations: Decorations::default(),
+        }
+    }
+}
+
+impl Theme {
+    pub fn builtin(name: &str) -> Option<Self> {
+        let (_, theme) = BUILTIN.iter().find(|(builtin, _)| *builtin == name)?;
+        Self::parse(theme).ok()
+    }
+
+    pub fn builtin_names() -> impl Iterator<Item = &'static str> {
+        BUILTIN.iter().map(|(name, _)| *name)
+    }
+
+    /// a built-in name or the path of a theme file
+    pub fn load(name: &str) -> Result<Self> {
+        match Self::builtin(name) {
+            Some(theme) => Ok(theme),
+            None => Self::parse(&std::fs::read_to_string(name)?),
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut theme = Self::default();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default();
+            let mut words = line.split_whitespace();
+            let Some(name) = words.next() else {
+                continue;
+            };
+            let error = || InvalidTheme(format!("line {}: {}", i + 1, line.trim()));
+            if Decoration::KEYWORDS.contains(&name) {
+                let words: Vec<_> = words.collect();
+                let decoration = Decoration::parse(name, &words).ok_or_else(error)?;
+                theme.decorations.set(decoration);
+                continue;
+            }
+            let channels = words
+                .map(|word| word.parse::<u8>())
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map_err(|_| error())?;
+            let &[r, g, b] = channels.as_slice() else {
+                return Err(Box::new(error()));
+            };
+            let color = Color::RGB(r, g, b);
+            match name {
+                "background" => theme.background = color,
+                "ball" => theme.ball = color,
+                "mid-line" => theme.mid_line = color,
+                "display" => theme.display = color,
+                "obstacle" => theme.obstacle = color,
+                "zone" => theme.zone = color,
+                "paddle" => theme.paddles = [color; 4],
+                "paddle-left" => theme.paddles[TeamName::Left.index()] = color,
+                "paddle-right" => theme.paddles[TeamName::Right.index()] = color,
+                "paddle-top" => theme.paddles[TeamName::Top.index()] = color,
+                "paddle-bottom" => theme.paddles[TeamName::Bottom.index()] = color,
+                _ => return Err(Box::new(error())),
+            }
+        }
+        Ok(theme)
+    }
+
+    pub fn paddle(&self, team: TeamName, paused: bool) -> Color {
+        self.paused(self.paddles[team.index()], paused)
+    }
+
+    pub fn ball(&self, paused: bool) -> Color {
+        self.paused(self.ball, paused)
+    }
+
+    /// halfway to the background while paused
+    fn paused(&self, color: Color, paused: bool) -> Color {
+        if !paused {
+            return color;
+        }
+        let half = |channel: u8, background: u8| ((channel as u16 + background as u16) / 2) as u8;
+        Color::RGB(
+            half(color.r, self.background.r),
+            half(color.g, self.background.g),
+            half(color.b, self.background.b),
+        )
+    }
+}
+
+#[test]
+fn test() {
+    use crate::decorations::LineStyle;
+
+    for name in Theme::builtin_names() {
+        assert!(Theme::builtin(name).is_some());
+    }
+    let theme = Theme::default();
+    assert_eq!(theme.ball(true), Color::RGB(127, 127, 0));
+    assert_eq!(theme.paddle(TeamName::Top, true), Color::RGB(127, 127, 127));
+    let theme = Theme::parse("background 0 0 255 # blue\npaddle-right 255 0 0").unwrap();
+    assert_eq!(theme.paddle(TeamName::Right, false), Color::RGB(255, 0, 0));
+    assert_eq!(theme.paddle(TeamName::Right, true), Color::RGB(127, 0, 127));
+    assert_eq!(theme.paddle(TeamName::Left, false), PADDLE_COLOR);
+    assert!(Theme::parse("ball 1 2").is_err());
+    assert!(Theme::parse("ball 1 2 300").is_err());
+    assert!(Theme::parse("net 1 2 3").is_err());
+    let theme = Theme::parse("center-line dotted\nzone 0 0 64").unwrap();
+    assert_eq!(theme.decorations.center_line, LineStyle::Dotted);
+    assert_eq!(theme.zone, Color::RGB(0, 0, 64));
+    assert!(Theme::parse("center-line 1 2 3").is_err());
+}
@@ -0,0 +1,160 @@
+use sdl2::event::Event;
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+use sdl2::EventPump;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use crate::capture::Capture;
+use crate::constants::*;
+use crate::crt::Crt;
+use crate::game::toggle_fullscreen;
+use crate::game_status::GameStatus;
+use crate::mode::Mode;
+use crate::point_display::PointDisplay;
+use crate::rules::Rules;
+use crate::spectator::SpectatorClient;
+use crate::team::TeamName;
+use crate::theme::Theme;
+use crate::Result;
+
+/// draws the match streamed by a `SpectatorServer`
+pub struct Viewer<'a> {
+    canvas: Canvas<Window>,
+    point_display: PointDisplay<'a>,
+    events: EventPump,
+    client: SpectatorClient,
+    fps: u64,
+    capture: Capture,
+    theme: Theme,
+    crt: Option<Crt>,
+    /// the match of the host the point display is made for
+    shown_match: Option<(Mode, Rules)>,
+}
+
+impl<'a> Viewer<'a> {
+    pub fn new(
+        canvas: Canvas<Window>,
+        point_display: PointDisplay<'a>,
+        events: EventPump,
+        client: SpectatorClient,
+        fps: u64,
+    ) -> Self {
+        Self {
+            canvas,
+            point_display,
+            events,
+            client,
+            fps,
+            capture: Capture::default(),
+            theme: Theme::default(),
+            crt: None,
+            shown_match: None,
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    pub fn set_crt(&mut self, crt: bool) {
+        self.crt = crt.then(Crt::new);
+    }
+
+    pub fn set_capture(&mut self, capture: Capture) {
+        self.capture = capture;
+    }
+
+    pub fn spawn(&mut self) -> Result<()> {
+        let frame_duration = Duration::from_nanos(1_000_000_000u64 / self.fps);
+        loop {
+            let loop_start_time = SystemTime::now();
+            for event in self.events.poll_iter() {
+                match event {
+                    Event::Quit { .. }
+                    | Event::KeyDown {
+                        keycode: Some(QUIT),
+                        ..
+                    } => return self.stop(),
+                    Event::KeyDown {
+                        keycode: Some(SCREENSHOT),
+                        ..
+                    } => self.capture.screenshot(),
+                    Event::KeyDown {
+                        keycode: Some(FULLSCREEN),
+                        ..
+                    } => toggle_fullscreen(&mut self.canvas)?,
+                    _ => {}
+                }
+            }
+            if self.client.is_connected() {
+                self.client.poll()?;
+            }
+            self.draw()?;
+            if self.capture.is_done() {
+                return self.stop();
+            }
+            let elapsed_time = SystemTime::now().duration_since(loop_start_time)?;
+            if frame_duration > elapsed_time {
+                thread::sleep(frame_duration - elapsed_time);
+            }
+        }
+    }
+
+    fn stop(&mut self) -> Result<()> {
+        if self.capture.stop()? {
+            self.draw()?;
+        }
+        Ok(())
+    }
+
+    fn draw(&mut self) -> Result<()> {
+        self.canvas.set_draw_color(self.theme.background);
+        self.canvas.clear();
+        if let Some(snapshot) = self.client.view() {
+            if self.shown_match != Some((snapshot.mode, snapshot.rules)) {
+                self.point_display.set_teams(snapshot.mode.counters())?;
+                self.point_display.show_sets(snapshot.rules.sets > 1);
+                self.shown_match = Some((snapshot.mode, snapshot.rules));
+            }
+            for team in TeamName::ALL {
+                self.point_display
+                    .set_point(team, snapshot.points[team.index()])?;
+                self.point_display
+                    .set_sets(team, snapshot.sets[team.index()])?;
+            }
+            let decorations = self.theme.decorations.with(&snapshot.decorations);
+            let teams: Vec<_> = TeamName::ALL
+                .into_iter()
+                .filter(|team| snapshot.teams.contains(team))
+                .collect();
+            self.canvas.set_draw_color(self.theme.zone);
+            self.canvas.fill_rects(&decorations.zones(&teams))?;
+            self.canvas.set_draw_color(self.theme.mid_line);
+            self.canvas.fill_rects(&decorations.lines(&teams))?;
+            self.point_display.draw(&mut self.canvas)?;
+            self.canvas.set_draw_color(self.theme.obstacle);
+            self.canvas.fill_rects(&snapshot.obstacles)?;
+            for wall in &snapshot.walls {
+                self.canvas.draw_line(wall.start(), wall.end())?;
+            }
+            for pickup in &snapshot.pickups {
+                self.canvas.set_draw_color(pickup.kind.color());
+                self.canvas.fill_rect(pickup.rect)?;
+            }
+            let paused = snapshot.status != GameStatus::Play || !self.client.is_connected();
+            for (paddle, team) in snapshot.paddles.iter().zip(&snapshot.teams) {
+                self.canvas.set_draw_color(self.theme.paddle(*team, paused));
+                self.canvas.fill_rect(*paddle)?;
+            }
+            self.canvas.set_draw_color(self.theme.ball(paused));
+            self.canvas.fill_rects(&snapshot.balls)?;
+        }
+        if let Some(crt) = &mut self.crt {
+            crt.draw(&mut self.canvas)?;
+        }
+        self.capture.frame(&mut self.canvas)?;
+        self.canvas.present();
+        Ok(())
+    }
+}
@@ -0,0 +1,1410 @@
+use crate::arena::{Arena, Court};
+use crate::ball::Ball;
+use crate::bricks::Bricks;
+use crate::constants::*;
+use crate::error::InvalidPacket;
+use crate::mode::Mode;
+use crate::packet::{PacketReader, PacketWriter};
+use crate::paddle::{Paddle, PaddleMove};
+use crate::powerup::{PowerUpKind, PowerUps};
+use crate::rules::Rules;
+use crate::team::TeamName;
+use crate::Result;
+
+/// how many balls are in play
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MultiBall {
+    /// balls served at the start of a match
+    pub count: usize,
+    /// a new ball is served every `spawn` ticks, up to `MAX_BALLS`
+    pub spawn: Option<u32>,
+    /// balls bounce off each other
+    pub collisions: bool,
+}
+
+impl Default for MultiBall {
+    fn default() -> Self {
+        Self {
+            count: 1,
+            spawn: None,
+            collisions: false,
+        }
+    }
+}
+
+/// how the balls get faster during a rally, the speeds are in percent of the serve speed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RallySpeed {
+    /// added at every paddle hit
+    pub per_hit: u32,
+    /// `(hits, percent)`: added once the rally reaches `hits`
+    pub tiers: Vec<(u32, u32)>,
+    /// the fastest a ball can go
+    pub cap: u32,
+}
+
+impl Default for RallySpeed {
+    fn default() -> Self {
+        Self {
+            per_hit: 0,
+            tiers: vec![],
+            cap: RALLY_SPEED_CAP,
+        }
+    }
+}
+
+impl RallySpeed {
+    pub fn factor(&self, hits: u32) -> f32 {
+        let tiers: u32 = self
+            .tiers
+            .iter()
+            .filter(|(tier, _)| hits >= *tier)
+            .map(|(_, percent)| percent)
+            .sum();
+        let percent = (100 + self.per_hit * hits + tiers).min(self.cap.max(100));
+        percent as f32 / 100.0
+    }
+}
+
+/// what the balls touched during the last tick, for the effects
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hit {
+    Paddle,
+    Wall,
+    Brick,
+    /// the team that conceded
+    Goal(TeamName),
+}
+
+impl Hit {
+    fn to_byte(self) -> u8 {
+        match self {
+            Hit::Paddle => 0,
+            Hit::Wall => 1,
+            Hit::Brick => 2,
+            Hit::Goal(team) => 3 + team.index() as u8,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(Hit::Paddle),
+            1 => Some(Hit::Wall),
+            2 => Some(Hit::Brick),
+            _ => TeamName::ALL
+                .get(byte as usize - 3)
+                .map(|team| Hit::Goal(*team)),
+        }
+    }
+}
+
+/// who can be credited for a ball
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct Possession {
+    /// index of the last paddle that hit the ball
+    last_hit: Option<usize>,
+    /// the ball moves with a sticky paddle for some ticks
+    held: Option<(usize, u32)>,
+    /// the held ball is served when it is released
+    serving: bool,
+    /// paddle hits since the ball was served
+    rally: u32,
+    /// id of the multi-ball that gave the ball, it leaves when that one runs out
+    bonus: Option<u32>,
+}
+
+/// everything that is simulated: given the same seed and the same inputs
+/// two worlds stay identical tick after tick
+#[derive(Debug, Clone, PartialEq)]
+pub struct World {
+    mode: Mode,
+    seed: u64,
+    multi_ball: MultiBall,
+    rally_speed: RallySpeed,
+    arena: Arena,
+    bricks: Bricks,
+    rules: Rules,
+    /// after a goal the ball waits on the paddle of the server for this many ticks
+    serve_timeout: Option<u32>,
+    /// the paddles give spin to the ball they hit
+    spin: bool,
+    /// ticks the balls wait before each serve, the paddles can move
+    countdown: u32,
+    /// ticks left of the current countdown
+    frozen: u32,
+    tick: u32,
+    paddles: Vec<Paddle>,
+    balls: Vec<Ball>,
+    /// one for each ball
+    possession: Vec<Possession>,
+    power_ups: PowerUps,
+    /// points scored in a classic match, lives left in a four player one
+    points: [u32; 4],
+    /// sets won, the last set of the match is not counted
+    sets: [u32; 4],
+    /// the tick the current set started at
+    set_start: u32,
+    /// with the centre of the ball
+    hits: Vec<(Hit, (i32, i32))>,
+}
+
+impl World {
+    pub fn new(mode: Mode, seed: u64) -> Self {
+        let mut world = Self {
+            mode,
+            seed,
+            multi_ball: MultiBall::default(),
+            rally_speed: RallySpeed::default(),
+            arena: Arena::default(),
+            bricks: Bricks::default(),
+            rules: Rules::default(),
+            serve_timeout: None,
+            countdown: 0,
+            frozen: 0,
+            spin: false,
+            tick: 0,
+            paddles: default_paddles(mode),
+            balls: vec![new_ball(seed)],
+            possession: vec![Possession::default()],
+            power_ups: PowerUps::new(seed.rotate_left(32)),
+            points: [0; 4],
+            sets: [0; 4],
+            set_start: 0,
+            hits: vec![],
+        };
+        world.reset_points();
+        world
+    }
+
+    /// restarts the match
+    pub fn set_multi_ball(&mut self, multi_ball: MultiBall) {
+        self.multi_ball = multi_ball;
+        self.reset();
+    }
+
+    /// restarts the match
+    pub fn set_rally_speed(&mut self, rally_speed: RallySpeed) {
+        self.rally_speed = rally_speed;
+        self.reset();
+    }
+
+    /// restarts the match
+    pub fn set_power_ups(&mut self, enabled: &[PowerUpKind]) {
+        self.power_ups.set_enabled(enabled);
+        self.reset();
+    }
+
+    /// restarts the match
+    pub fn set_arena(&mut self, arena: Arena) {
+        self.arena = arena;
+        self.reset();
+    }
+
+    /// restarts the match
+    pub fn set_rules(&mut self, rules: Rules) {
+        self.rules = rules;
+        self.reset();
+    }
+
+    /// `None` serves the ball from the centre right after a goal; only two teams
+    /// matches have a serve phase
+    pub fn set_serve_timeout(&mut self, timeout: Option<u32>) {
+        self.serve_timeout = timeout;
+        self.reset();
+    }
+
+    /// in ticks, before the first serve, after every goal and after `freeze`
+    pub fn set_countdown(&mut self, ticks: u32) {
+        self.countdown = ticks;
+        self.reset();
+    }
+
+    /// the balls wait at least `ticks` and the countdown before a serve, e.g.
+    /// when the game is resumed
+    pub fn freeze(&mut self, ticks: u32) {
+        self.frozen = self.frozen.max(ticks).max(self.countdown);
+    }
+
+    /// the balls move right away, until the next goal
+    pub fn skip_countdown(&mut self) {
+        self.frozen = 0;
+    }
+
+    /// the ticks left before the balls move again
+    pub fn countdown_left(&self) -> u32 {
+        self.frozen
+    }
+
+    /// restarts the match
+    pub fn set_spin(&mut self, spin: bool) {
+        self.spin = spin;
+        self.reset();
+    }
+
+    /// launches the balls waiting on the paddles of `team` during the next step
+    pub fn serve(&mut self, team: TeamName) {
+        for possession in &mut self.possession {
+            match &mut possession.held {
+                Some((paddle, ticks))
+                    if possession.serving && self.paddles[*paddle].team() == team =>
+                {
+                    *ticks = 1
+                }
+                _ => {}
+            }
+        }
+    }
+
+    pub fn is_serving(&self, team: TeamName) -> bool {
+        self.possession.iter().any(|possession| {
+            possession.serving
+                && possession
+                    .held
+                    .is_some_and(|(paddle, _)| self.paddles[paddle].team() == team)
+        })
+    }
+
+    pub fn rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    /// restarts the match
+    pub fn set_bricks(&mut self, bricks: Bricks) {
+        self.bricks = bricks;
+        self.reset();
+    }
+
+    pub fn bricks(&self) -> &Bricks {
+        &self.bricks
+    }
+
+    pub fn arena(&self) -> &Arena {
+        &self.arena
+    }
+
+    pub fn tick(&self) -> u32 {
+        self.tick
+    }
+
+    /// the hits of the last tick, with where they happened
+    pub fn hits(&self) -> &[(Hit, (i32, i32))] {
+        &self.hits
+    }
+
+    pub fn power_ups(&self) -> &PowerUps {
+        &self.power_ups
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// ordered by team, the back paddle of a team comes first; eliminated ones are included
+    pub fn paddles(&self) -> &[Paddle] {
+        &self.paddles
+    }
+
+    pub fn active_paddles(&self) -> impl Iterator<Item = &Paddle> {
+        self.paddles
+            .iter()
+            .filter(|paddle| self.is_alive(paddle.team()))
+    }
+
+    pub fn balls(&self) -> &[Ball] {
+        &self.balls
+    }
+
+    pub fn points(&self, team: TeamName) -> u32 {
+        self.points[team.index()]
+    }
+
+    pub fn sets(&self, team: TeamName) -> u32 {
+        self.sets[team.index()]
+    }
+
+    /// in ticks, for the current set
+    pub fn time_left(&self) -> Option<u32> {
+        let played = self.tick - self.set_start;
+        self.rules
+            .time_limit
+            .map(|limit| limit.saturating_sub(played))
+    }
+
+    /// what the balls bounce on in the current tick, the teams still in play have a goal
+    pub fn court(&self) -> Court {
+        let goals: Vec<_> = self
+            .mode
+            .teams()
+            .iter()
+            .copied()
+            .filter(|team| self.is_alive(*team))
+            .collect();
+        let mut court = self.arena.court(self.tick, &goals);
+        court.bricks = self.bricks.alive().map(|(i, rect, _)| (i, rect)).collect();
+        court
+    }
+
+    pub fn is_alive(&self, team: TeamName) -> bool {
+        !self.mode.has_lives() || self.points(team) > 0
+    }
+
+    pub fn winner(&self) -> Option<TeamName> {
+        let mut teams = self.mode.teams().iter().copied();
+        if self.mode.is_solo() {
+            return None;
+        }
+        if !self.mode.has_lives() {
+            let set_winner = self.set_winner();
+            return teams.find(|team| {
+                self.sets(*team) + (set_winner == Some(*team)) as u32 >= self.rules.sets_to_win()
+            });
+        }
+        let mut alive = teams.filter(|team| self.is_alive(*team));
+        match (alive.next(), alive.next()) {
+            (Some(team), None) => Some(team),
+            _ => None,
+        }
+    }
+
+    /// the random stream of the ball is not restarted
+    pub fn reset(&mut self) {
+        self.paddles = default_paddles(self.mode);
+        self.tick = 0;
+        self.balls.truncate(self.multi_ball.count.max(1));
+        while self.balls.len() < self.multi_ball.count {
+            self.spawn_ball();
+        }
+        for ball in &mut self.balls {
+            ball.reset_rng();
+        }
+        self.possession = vec![Possession::default(); self.balls.len()];
+        self.power_ups.clear();
+        self.bricks.reset();
+        self.apply_effects();
+        self.reset_points();
+        self.frozen = self.countdown;
+    }
+
+    pub fn reset_points(&mut self) {
+        self.points = if self.mode.has_lives() {
+            [LIVES; 4]
+        } else {
+            [0; 4]
+        };
+        self.sets = [0; 4];
+        self.set_start = self.tick;
+    }
+
+    fn set_winner(&self) -> Option<TeamName> {
+        let points: Vec<_> = self
+            .mode
+            .teams()
+            .iter()
+            .map(|team| (*team, self.points(*team)))
+            .collect();
+        self.rules.set_winner(&points, self.tick - self.set_start)
+    }
+
+    /// a won set is counted and a new one starts, unless it wins the match
+    fn update_sets(&mut self) {
+        if self.mode.has_lives() || self.mode.is_solo() || self.winner().is_some() {
+            return;
+        }
+        if let Some(team) = self.set_winner() {
+            self.sets[team.index()] += 1;
+            self.points = [0; 4];
+            self.set_start = self.tick;
+        }
+    }
+
+    /// the seed of a ball depends only on its position in the list
+    fn spawn_ball(&mut self) {
+        let seed = self.seed.wrapping_add(self.balls.len() as u64);
+        let mut ball = new_ball(seed);
+        if let Some(first) = self.balls.first() {
+            ball.set_speed(first.vx_default(), first.vy_default());
+        }
+        self.balls.push(ball);
+        self.possession.push(Possession::default());
+    }
+
+    /// paddle lengths and ball speeds follow the effects in play and the rallies
+    fn apply_effects(&mut self) {
+        for i in 0..self.paddles.len() {
+            let team = self.paddles[i].team();
+            let same_team = |paddle: usize| self.paddles[paddle].team() == team;
+            let grow = self
+                .power_ups
+                .count(PowerUpKind::Grow, |paddle| paddle == i);
+            let shrink = self
+                .power_ups
+                .count(PowerUpKind::Shrink, |paddle| !same_team(paddle));
+            let length =
+                default_paddle(team).length() as f32 * GROW.powi(grow) * SHRINK.powi(shrink);
+            let max = if self.paddles[i].is_horizontal() {
+                WINDOW_WIDTH
+            } else {
+                WINDOW_HEIGHT
+            };
+            if length as u32 != self.paddles[i].length() {
+                self.paddles[i].set_length(length as u32, 0, max as i32);
+            }
+        }
+        let fast = self.power_ups.count(PowerUpKind::SpeedUp, |_| true);
+        let slow = self.power_ups.count(PowerUpKind::SlowDown, |_| true);
+        let factor = SPEED_UP.powi(fast) * SLOW_DOWN.powi(slow);
+        for (ball, possession) in self.balls.iter_mut().zip(&self.possession) {
+            let factor = factor * self.rally_speed.factor(possession.rally);
+            let (vx, vy) = (
+                (BALL_VX as f32 * factor) as i32,
+                (BALL_VY as f32 * factor) as i32,
+            );
+            if (ball.vx_default(), ball.vy_default()) != (vx, vy) {
+                ball.set_speed(vx, vy);
+            }
+        }
+    }
+
+    /// power-ups are credited to the last paddle that hit the ball; returns the
+    /// indexes of the balls that have left the court, in order
+    fn collect_power_ups(&mut self) -> Vec<usize> {
+        for i in 0..self.balls.len() {
+            let Some(paddle) = self.possession[i].last_hit else {
+                continue;
+            };
+            let Some(kind) = self.power_ups.collect(self.balls[i].rect()) else {
+                continue;
+            };
+            let id = self.power_ups.add(kind, paddle);
+            if kind == PowerUpKind::MultiBall {
+                self.give_ball(id);
+            }
+        }
+        let mut taken = vec![];
+        for effect in self.power_ups.tick() {
+            if effect.kind == PowerUpKind::MultiBall {
+                taken.extend(self.take_ball(effect.id));
+            }
+        }
+        self.apply_effects();
+        taken
+    }
+
+    /// a bonus ball for the multi-ball `id`, none when the court is full
+    fn give_ball(&mut self, id: u32) {
+        if self.balls.len() < MAX_BALLS {
+            self.spawn_ball();
+            if let Some(possession) = self.possession.last_mut() {
+                possession.bonus = Some(id);
+            }
+        }
+    }
+
+    /// the bonus ball of the multi-ball `id` leaves the court when it runs out,
+    /// the balls of the match and of the other multi-balls stay
+    fn take_ball(&mut self, id: u32) -> Option<usize> {
+        let i = self
+            .possession
+            .iter()
+            .position(|possession| possession.bonus == Some(id))?;
+        self.balls.remove(i);
+        self.possession.remove(i);
+        Some(i)
+    }
+
+    /// the ball waits in front of the back paddle of `server`
+    fn hold_serve(&mut self, i: usize, server: TeamName, timeout: u32) {
+        let Some(paddle) = self
+            .paddles
+            .iter()
+            .position(|paddle| paddle.team() == server)
+        else {
+            return;
+        };
+        let rect = self.paddles[paddle].rect();
+        let ball = &mut self.balls[i];
+        let x = rect.x() + (rect.width() as i32 - ball.width()) / 2;
+        let y = rect.y() + (rect.height() as i32 - ball.height()) / 2;
+        let (x, y) = match server {
+            TeamName::Left => (rect.right(), y),
+            TeamName::Right => (rect.x() - ball.width(), y),
+            TeamName::Top => (x, rect.bottom()),
+            TeamName::Bottom => (x, rect.y() - ball.height()),
+        };
+        ball.set_motion(x, y, 0, 0);
+        self.possession[i] = Possession {
+            last_hit: Some(paddle),
+            held: Some((paddle, timeout)),
+            serving: true,
+            rally: 0,
+            bonus: self.possession[i].bonus,
+        };
+    }
+
+    /// a moving obstacle carries the balls it runs into
+    fn move_obstacles(&mut self) {
+        let before = self.arena.obstacles(self.tick - 1);
+        let after = self.arena.obstacles(self.tick);
+        for (before, after) in before.iter().zip(&after) {
+            let (dx, dy) = (after.x() - before.x(), after.y() - before.y());
+            if (dx, dy) == (0, 0) {
+                continue;
+            }
+            for ball in &mut self.balls {
+                let rect = ball.rect();
+                let overlap_x = rect.right().min(after.right()) - rect.x().max(after.x());
+                let overlap_y = rect.bottom().min(after.bottom()) - rect.y().max(after.y());
+                if overlap_x <= 0 || overlap_y <= 0 {
+                    continue;
+                }
+                let x = (ball.x() + dx).clamp(0, WINDOW_WIDTH as i32 - ball.width());
+                let y = (ball.y() + dy).clamp(0, WINDOW_HEIGHT as i32 - ball.height());
+                ball.set_motion(x, y, ball.vx(), ball.vy());
+            }
+        }
+    }
+
+    /// `moves` has one element for each paddle, returns the teams that conceded a goal
+    pub fn step(&mut self, moves: &[PaddleMove]) -> Result<Vec<TeamName>> {
+        self.hits.clear();
+        // during a countdown only the paddles move, the time of the match waits
+        // with the balls, so do the obstacles and every timer
+        let frozen = self.frozen > 0;
+        self.frozen = self.frozen.saturating_sub(1);
+        if !frozen {
+            self.tick += 1;
+            if let Some(spawn) = self.multi_ball.spawn {
+                if self.tick.is_multiple_of(spawn.max(1)) && self.balls.len() < MAX_BALLS {
+                    self.spawn_ball();
+                }
+            }
+            self.power_ups.spawn(self.tick);
+        }
+
+        let alive = TeamName::ALL.map(|team| self.is_alive(team));
+        let inverted = TeamName::ALL.map(|team| {
+            self.power_ups.count(PowerUpKind::Invert, |paddle| {
+                self.paddles[paddle].team() != team
+            }) > 0
+        });
+        let before: Vec<_> = self.paddles.iter().map(|paddle| paddle.rect()).collect();
+        for (paddle, movement) in self.paddles.iter_mut().zip(moves) {
+            if !alive[paddle.team().index()] {
+                continue;
+            }
+            let max = if paddle.is_horizontal() {
+                WINDOW_WIDTH
+            } else {
+                WINDOW_HEIGHT
+            };
+            let movement = if inverted[paddle.team().index()] {
+                movement.inverted()
+            } else {
+                *movement
+            };
+            paddle.apply(movement, 0, max as i32, &mut self.balls);
+        }
+
+        if !frozen {
+            self.move_obstacles();
+        }
+
+        let active: Vec<_> = (0..self.paddles.len())
+            .filter(|i| alive[self.paddles[*i].team().index()])
+            .collect();
+        let paddles: Vec<_> = active.iter().map(|i| &self.paddles[*i]).collect();
+        let court = self.court();
+        // bricks only score in a match between two teams
+        let brick_points = !self.mode.has_lives() && !self.mode.is_solo();
+        let mut goals_hit = vec![];
+        for (i, ball) in self.balls.iter_mut().enumerate() {
+            let possession = &mut self.possession[i];
+            if let Some((paddle, ticks)) = possession.held {
+                let rect = self.paddles[paddle].rect();
+                let (dx, dy) = (rect.x() - before[paddle].x(), rect.y() - before[paddle].y());
+                ball.set_motion(ball.x() + dx, ball.y() + dy, ball.vx(), ball.vy());
+                if frozen {
+                    continue;
+                }
+                possession.held = (ticks > 1).then_some((paddle, ticks - 1));
+                if possession.held.is_none() && possession.serving {
+                    // the paddle moving while serving gives the angle
+                    let server = &self.paddles[paddle];
+                    let spin = if server.is_horizontal() { dx } else { dy };
+                    ball.serve(server.team(), spin.signum());
+                    possession.serving = false;
+                }
+                continue;
+            }
+            if frozen {
+                continue;
+            }
+            let contacts = ball.change_position(&paddles, &court)?;
+            let hits = [
+                contacts.paddle.map(|_| Hit::Paddle),
+                contacts.wall.then_some(Hit::Wall),
+                contacts.brick.map(|_| Hit::Brick),
+                contacts.goal.map(Hit::Goal),
+            ];
+            for hit in hits.into_iter().flatten() {
+                self.hits.push((hit, ball.center()));
+            }
+            if let Some(paddle) = contacts.paddle.map(|j| active[j]) {
+                possession.last_hit = Some(paddle);
+                possession.rally += 1;
+                if self.mode.is_solo() {
+                    self.points[TeamName::Left.index()] += 1;
+                }
+                if self.spin {
+                    // the movement of the paddle during the tick
+                    let rect = self.paddles[paddle].rect();
+                    let (dx, dy) = (rect.x() - before[paddle].x(), rect.y() - before[paddle].y());
+                    ball.set_spin(dx * SPIN_TRANSFER, dy * SPIN_TRANSFER);
+                }
+                if self
+                    .power_ups
+                    .count(PowerUpKind::Sticky, |sticky| sticky == paddle)
+                    > 0
+                {
+                    possession.held = Some((paddle, STICKY_HOLD));
+                }
+            }
+            if let Some(points) = contacts.brick.and_then(|brick| self.bricks.hit(brick)) {
+                if let Some(paddle) = possession.last_hit.filter(|_| brick_points) {
+                    self.points[self.paddles[paddle].team().index()] += points;
+                }
+            }
+            if let Some(team) = contacts.goal {
+                goals_hit.push((i, team));
+            }
+        }
+        if self.bricks.is_cleared() {
+            self.bricks.reset();
+        }
+        if frozen {
+            // nothing but the paddles has moved
+            return Ok(vec![]);
+        }
+        if self.multi_ball.collisions {
+            for i in 1..self.balls.len() {
+                let (before, after) = self.balls.split_at_mut(i);
+                for other in before {
+                    after[0].collide(other);
+                }
+            }
+        }
+
+        // a multi-ball may have just expired, the goals follow the balls left
+        for taken in self.collect_power_ups() {
+            goals_hit.retain(|(i, _)| *i != taken);
+            for (i, _) in &mut goals_hit {
+                if *i > taken {
+                    *i -= 1;
+                }
+            }
+        }
+
+        let mut conceded = vec![];
+        for (i, team) in goals_hit {
+            self.possession[i] = Possession {
+                bonus: self.possession[i].bonus,
+                ..Possession::default()
+            };
+            // another ball may have already eliminated the team
+            if !self.is_alive(team) {
+                continue;
+            }
+            conceded.push(team);
+            let direction = if self.mode.has_lives() {
+                self.points[team.index()] -= 1;
+                if self.is_alive(team) {
+                    team
+                } else {
+                    court
+                        .goals
+                        .iter()
+                        .map(|(goal, ..)| *goal)
+                        .find(|goal| *goal != team)
+                        .unwrap_or(team)
+                }
+            } else if self.mode.is_solo() {
+                // the streak is over, the next serve goes to the wall
+                self.points[team.index()] = 0;
+                team.opponent()
+            } else {
+                self.points[team.opponent().index()] += 1;
+                let played = self.points.iter().sum();
+                self.rules.server(team, played).opponent()
+            };
+            match self.serve_timeout {
+                Some(timeout) if !self.mode.has_lives() => {
+                    self.hold_serve(i, direction.opponent(), timeout)
+                }
+                _ => self.balls[i].after_goal_rng(
+                    (WINDOW_WIDTH - BALL_DIAMETER) as i32 / 2,
+                    (WINDOW_HEIGHT - BALL_DIAMETER) as i32 / 2,
+                    direction,
+                ),
+            }
+        }
+        if !conceded.is_empty() {
+            // the rallies of the balls that scored are over
+            self.apply_effects();
+            self.frozen = self.countdown;
+        }
+        self.update_sets();
+        Ok(conceded)
+    }
+
+    pub fn write(&self, writer: &mut PacketWriter) {
+        for paddle in &self.paddles {
+            writer.put_i32(paddle.x());
+            writer.put_i32(paddle.y());
+            writer.put_u32(paddle.length());
+        }
+        writer.put_u32(self.tick);
+        writer.put_u8(self.balls.len() as u8);
+        for ball in &self.balls {
+            writer.put_i32(ball.x());
+            writer.put_i32(ball.y());
+            writer.put_i32(ball.vx());
+            writer.put_i32(ball.vy());
+            writer.put_i32(ball.vx_default());
+            writer.put_i32(ball.vy_default());
+            for (x, y) in [ball.spin(), ball.spin_rest()] {
+                writer.put_i32(x);
+                writer.put_i32(y);
+            }
+            writer.put_u128(ball.rng_position());
+        }
+        for possession in &self.possession {
+            writer.put_u8(possession.last_hit.map_or(u8::MAX, |paddle| paddle as u8));
+            let (paddle, ticks) = possession.held.unwrap_or((u8::MAX as usize, 0));
+            writer.put_u8(paddle as u8);
+            writer.put_u32(ticks);
+            writer.put_u8(possession.serving as u8);
+            writer.put_u32(possession.rally);
+            writer.put_u8(possession.bonus.is_some() as u8);
+            writer.put_u32(possession.bonus.unwrap_or(0));
+        }
+        self.power_ups.write(writer);
+        self.bricks.write(writer);
+        for points in self.points.iter().chain(&self.sets) {
+            writer.put_u32(*points);
+        }
+        writer.put_u32(self.set_start);
+        writer.put_u32(self.frozen);
+        writer.put_u8(self.hits.len() as u8);
+        for (hit, (x, y)) in &self.hits {
+            writer.put_u8(hit.to_byte());
+            writer.put_i32(*x);
+            writer.put_i32(*y);
+        }
+    }
+
+    pub fn read(&mut self, reader: &mut PacketReader) -> Result<()> {
+        for paddle in &mut self.paddles {
+            let x = reader.get_i32()?;
+            let y = reader.get_i32()?;
+            let max = if paddle.is_horizontal() {
+                WINDOW_WIDTH
+            } else {
+                WINDOW_HEIGHT
+            };
+            paddle.set_length(reader.get_u32()?, 0, max as i32);
+            paddle.set_position(x, y);
+        }
+        self.tick = reader.get_u32()?;
+        let balls = reader.get_u8()? as usize;
+        if balls > MAX_BALLS {
+            return Err(Box::new(InvalidPacket));
+        }
+        self.balls.truncate(balls);
+        self.possession.truncate(balls);
+        while self.balls.len() < balls {
+            self.spawn_ball();
+        }
+        for ball in &mut self.balls {
+            let x = reader.get_i32()?;
+            let y = reader.get_i32()?;
+            let vx = reader.get_i32()?;
+            let vy = reader.get_i32()?;
+            let vx_default = reader.get_i32()?;
+            ball.set_speed(vx_default, reader.get_i32()?);
+            ball.set_motion(x, y, vx, vy);
+            let spin_x = reader.get_i32()?;
+            ball.set_spin(spin_x, reader.get_i32()?);
+            let rest_x = reader.get_i32()?;
+            ball.set_spin_rest(rest_x, reader.get_i32()?);
+            ball.set_rng_position(reader.get_u128()?);
+        }
+        // u8::MAX for none, any other index has to be one of a paddle
+        let paddles = self.paddles.len();
+        let index = |byte: u8| match byte as usize {
+            index if index < paddles => Ok(Some(index)),
+            _ if byte == u8::MAX => Ok(None),
+            _ => Err(InvalidPacket),
+        };
+        for possession in &mut self.possession {
+            possession.last_hit = index(reader.get_u8()?)?;
+            let paddle = index(reader.get_u8()?)?;
+            let ticks = reader.get_u32()?;
+            possession.held = paddle.map(|paddle| (paddle, ticks));
+            possession.serving = reader.get_u8()? != 0;
+            possession.rally = reader.get_u32()?;
+            let bonus = reader.get_u8()? != 0;
+            let id = reader.get_u32()?;
+            possession.bonus = bonus.then_some(id);
+        }
+        self.power_ups.read(reader, paddles)?;
+        self.bricks.read(reader)?;
+        for points in self.points.iter_mut().chain(&mut self.sets) {
+            *points = reader.get_u32()?;
+        }
+        self.set_start = reader.get_u32()?;
+        // a set can't start in the future
+        if self.set_start > self.tick {
+            return Err(Box::new(InvalidPacket));
+        }
+        self.frozen = reader.get_u32()?;
+        self.hits.clear();
+        for _ in 0..reader.get_u8()? {
+            let hit = Hit::from_byte(reader.get_u8()?).ok_or(InvalidPacket)?;
+            let x = reader.get_i32()?;
+            self.hits.push((hit, (x, reader.get_i32()?)));
+        }
+        Ok(())
+    }
+}
+
+fn new_ball(seed: u64) -> Ball {
+    Ball::new_rng(
+        (WINDOW_WIDTH - BALL_DIAMETER) as i32 / 2,
+        (WINDOW_HEIGHT - BALL_DIAMETER) as i32 / 2,
+        BALL_DIAMETER,
+        BALL_VX,
+        BALL_VY,
+        MULTIPLIER,
+        SLOW_START,
+    )
+    .with_seed(seed)
+}
+
+fn default_paddles(mode: Mode) -> Vec<Paddle> {
+    let mut paddles = vec![];
+    for team in mode.teams() {
+        paddles.push(default_paddle(*team));
+        if mode == Mode::Doubles {
+            paddles.push(forward_paddle(*team));
+        }
+    }
+    paddles
+}
+
+/// same size of the back paddle, closer to the mid line
+fn forward_paddle(team: TeamName) -> Paddle {
+    let back = default_paddle(team);
+    let (x, step) = match team {
+        TeamName::Right => (PADDLE_RF_X, PADDLE_R_STEP),
+        _ => (PADDLE_LF_X, PADDLE_L_STEP),
+    };
+    Paddle::new(
+        team,
+        x,
+        back.y(),
+        back.width() as u32,
+        back.height() as u32,
+        step,
+    )
+}
+
+fn default_paddle(team: TeamName) -> Paddle {
+    match team {
+        TeamName::Left => Paddle::new(
+            team,
+            PADDLE_L_X,
+            (WINDOW_HEIGHT - PADDLE_L_HEIGHT) as i32 / 2,
+            PADDLE_WIDTH,
+            PADDLE_L_HEIGHT,
+            PADDLE_L_STEP,
+        ),
+        TeamName::Right => Paddle::new(
+            team,
+            PADDLE_R_X,
+            (WINDOW_HEIGHT - PADDLE_R_HEIGHT) as i32 / 2,
+            PADDLE_WIDTH,
+            PADDLE_R_HEIGHT,
+            PADDLE_R_STEP,
+        ),
+        TeamName::Top => Paddle::new(
+            team,
+            (WINDOW_WIDTH - PADDLE_T_WIDTH) as i32 / 2,
+            PADDLE_T_Y,
+            PADDLE_T_WIDTH,
+            PADDLE_WIDTH,
+            PADDLE_T_STEP,
+        ),
+        TeamName::Bottom => Paddle::new(
+            team,
+            (WINDOW_WIDTH - PADDLE_B_WIDTH) as i32 / 2,
+            PADDLE_B_Y,
+            PADDLE_B_WIDTH,
+            PADDLE_WIDTH,
+            PADDLE_B_STEP,
+        ),
+    }
+}
+
+#[test]
+fn test() {
+    let moves = [PaddleMove::Up, PaddleMove::Stay, PaddleMove::Down];
+    let mut world_1 = World::new(Mode::Classic, 42);
+    let mut world_2 = World::new(Mode::Classic, 42);
+    for i in 0..2000 {
+        let moves = [moves[i % 3], moves[i / 7 % 3]];
+        world_1.step(&moves).unwrap();
+        world_2.step(&moves).unwrap();
+    }
+    assert_eq!(world_1, world_2);
+
+    let mut writer = PacketWriter::new(0);
+    world_1.write(&mut writer);
+    let mut world_3 = World::new(Mode::Classic, 42);
+    let mut reader = PacketReader::new(&writer.bytes()[1..]);
+    world_3.read(&mut reader).unwrap();
+    assert_eq!(world_1, world_3);
+
+    // a paddle that doesn't exist, a set that starts in the future
+    let mut wrong = world_1.clone();
+    wrong.possession[0].last_hit = Some(2);
+    let mut late = world_1.clone();
+    late.set_start = late.tick + 1;
+    for world in [wrong, late] {
+        let mut writer = PacketWriter::new(0);
+        world.write(&mut writer);
+        let mut reader = PacketReader::new(&writer.bytes()[1..]);
+        assert!(world_3.read(&mut reader).is_err());
+    }
+}
+
+#[test]
+fn test_multi_ball() {
+    let multi_ball = MultiBall {
+        count: 2,
+        spawn: Some(100),
+        collisions: true,
+    };
+    let mut world_1 = World::new(Mode::Classic, 7);
+    world_1.set_multi_ball(multi_ball);
+    let mut world_2 = world_1.clone();
+    assert_eq!(world_1.balls().len(), 2);
+    let moves = [PaddleMove::Up, PaddleMove::Stay, PaddleMove::Down];
+    for i in 0..1000 {
+        let moves = [moves[i / 13 % 3], moves[i / 17 % 3]];
+        world_1.step(&moves).unwrap();
+        world_2.step(&moves).unwrap();
+    }
+    assert_eq!(world_1.balls().len(), MAX_BALLS.min(12));
+    assert_eq!(world_1, world_2);
+
+    let mut writer = PacketWriter::new(0);
+    world_1.write(&mut writer);
+    let mut world_3 = World::new(Mode::Classic, 7);
+    world_3.set_multi_ball(multi_ball);
+    world_3
+        .read(&mut PacketReader::new(&writer.bytes()[1..]))
+        .unwrap();
+    assert_eq!(world_1, world_3);
+}
+
+#[test]
+fn test_power_ups() {
+    let mut world = World::new(Mode::Classic, 11);
+    world.set_power_ups(&PowerUpKind::ALL);
+    let moves = [PaddleMove::Up, PaddleMove::Stay, PaddleMove::Down];
+    let mut collected = 0;
+    let mut copy = world.clone();
+    for i in 0..20_000 {
+        let moves = [moves[i / 23 % 3], moves[i / 31 % 3]];
+        let effects = world.power_ups().effects().len();
+        world.step(&moves).unwrap();
+        if world.power_ups().effects().len() > effects {
+            collected += 1;
+        }
+        if i % 500 == 0 {
+            let mut writer = PacketWriter::new(0);
+            world.write(&mut writer);
+            copy.read(&mut PacketReader::new(&writer.bytes()[1..]))
+                .unwrap();
+            assert_eq!(world, copy);
+        }
+    }
+    assert!(collected > 0);
+
+    let left = world.paddles()[0].length();
+    world.power_ups.add(PowerUpKind::Grow, 0);
+    world.power_ups.add(PowerUpKind::Invert, 0);
+    world.apply_effects();
+    assert!(world.paddles()[0].length() > left);
+    let y = world.paddles()[1].y();
+    world.step(&[PaddleMove::Stay, PaddleMove::Down]).unwrap();
+    assert!(world.paddles()[1].y() <= y);
+}
+
+#[test]
+fn test_bonus_ball() {
+    let mut world = World::new(Mode::Classic, 3);
+    world.set_multi_ball(MultiBall {
+        count: MAX_BALLS,
+        spawn: None,
+        collisions: false,
+    });
+    // no room for a bonus ball, none of the balls of the match leaves
+    world.give_ball(0);
+    world.take_ball(0);
+    assert_eq!(world.balls().len(), MAX_BALLS);
+
+    world.set_multi_ball(MultiBall {
+        count: MAX_BALLS - 2,
+        spawn: None,
+        collisions: false,
+    });
+    world.give_ball(1);
+    world.give_ball(2);
+    let second = world.balls()[MAX_BALLS - 1].clone();
+    // the court is full, the third multi-ball has no ball to take back
+    world.give_ball(3);
+    world.take_ball(3);
+    assert_eq!(world.balls().len(), MAX_BALLS);
+    // the first one to run out takes its own ball, not the one of the second
+    world.take_ball(1);
+    assert_eq!(world.balls().len(), MAX_BALLS - 1);
+    assert_eq!(world.balls()[MAX_BALLS - 2], second);
+    world.take_ball(2);
+    world.take_ball(2);
+    assert_eq!(world.balls().len(), MAX_BALLS - 2);
+}
+
+#[test]
+fn test_doubles() {
+    let mut world = World::new(Mode::Doubles, 5);
+    assert_eq!(world.paddles().len(), 4);
+    let moves = [PaddleMove::Up, PaddleMove::Stay, PaddleMove::Down];
+    for i in 0..20_000 {
+        if world.winner().is_some() {
+            break;
+        }
+        let moves = [
+            moves[i / 30 % 3],
+            moves[i / 50 % 3],
+            moves[i / 70 % 3],
+            moves[i / 20 % 3],
+        ];
+        for team in world.step(&moves).unwrap() {
+            assert!(world.points(team.opponent()) > 0);
+        }
+    }
+    assert!(world.winner().is_some());
+    assert_eq!(world.points(TeamName::Top), 0);
+}
+
+#[test]
+fn test_four_player() {
+    let mut world = World::new(Mode::FourPlayer, 1);
+    let moves = [PaddleMove::Up, PaddleMove::Down];
+    let mut goals = 0;
+    for i in 0..100_000 {
+        if world.winner().is_some() {
+            break;
+        }
+        for team in world.step(&[moves[i / 40 % 2]; 4]).unwrap() {
+            assert!(world.points(team) < LIVES);
+            goals += 1;
+        }
+    }
+    assert!(world.winner().is_some());
+    assert_eq!(goals, 3 * LIVES);
+    assert_eq!(world.active_paddles().count(), 1);
+}
+
+#[test]
+fn test_arena() {
+    use crate::arena::Arena;
+
+    let mut world = World::new(Mode::Classic, 3);
+    world.set_arena(Arena::builtin("gates").unwrap());
+    let stay = [PaddleMove::Stay; 2];
+    // beside the mouth the ball bounces, inside it the goal counts
+    world.balls[0].set_motion(4, 20, -10, 0);
+    assert!(world.step(&stay).unwrap().is_empty());
+    assert!(world.balls()[0].vx() > 0);
+    world.balls[0].set_motion(4, WINDOW_HEIGHT as i32 / 2, -10, 0);
+    assert_eq!(world.step(&stay).unwrap(), [TeamName::Left]);
+
+    // a slanted wall turns a vertical motion into a diagonal one
+    world.set_arena(Arena::builtin("bumpers").unwrap());
+    let x = WINDOW_WIDTH as i32 * 460 / 1000 - BALL_DIAMETER as i32 / 2;
+    world.balls[0].set_motion(x, 80, 0, -8);
+    for _ in 0..10 {
+        world.step(&stay).unwrap();
+    }
+    assert!(world.balls()[0].vx() < 0 && world.balls()[0].y() > 10);
+
+    for name in Arena::builtin_names() {
+        let mut world = World::new(Mode::Classic, 9);
+        world.set_arena(Arena::builtin(name).unwrap());
+        let moves = [PaddleMove::Up, PaddleMove::Stay, PaddleMove::Down];
+        let mut goals = 0;
+        for i in 0..20_000 {
+            let moves = [moves[i / 23 % 3], moves[i / 31 % 3]];
+            goals += world.step(&moves).unwrap().len();
+            let ball = world.balls()[0].rect();
+            assert!(ball.x() >= 0 && ball.right() <= WINDOW_WIDTH as i32);
+            assert!(ball.y() >= 0 && ball.bottom() <= WINDOW_HEIGHT as i32);
+        }
+        assert!(goals > 0, "{name}");
+    }
+}
+
+#[test]
+fn test_rules() {
+    use crate::rules::{Rules, Serve};
+
+    let mut world = World::new(Mode::Classic, 4);
+    world.set_rules(Rules {
+        points_to_win: 2,
+        win_by: 2,
+        time_limit: None,
+        sets: 3,
+        serve: Serve::Alternate(1),
+    });
+    let moves = [PaddleMove::Up, PaddleMove::Stay, PaddleMove::Down];
+    for i in 0..100_000 {
+        if world.winner().is_some() {
+            break;
+        }
+        world.step(&[moves[i / 23 % 3], moves[i / 31 % 3]]).unwrap();
+    }
+    let winner = world.winner().unwrap();
+    assert_eq!(world.sets(winner), 1);
+    assert!(world.points(winner) >= world.points(winner.opponent()) + 2);
+
+    // when the time runs out the leader wins, or the next point does
+    let mut world = World::new(Mode::Classic, 4);
+    world.set_rules(Rules {
+        points_to_win: 100,
+        time_limit: Some(300),
+        ..Rules::default()
+    });
+    for i in 0..100_000 {
+        if world.winner().is_some() {
+            break;
+        }
+        world.step(&[moves[i / 23 % 3], moves[i / 31 % 3]]).unwrap();
+    }
+    assert_eq!(world.time_left(), Some(0));
+    assert!(world.winner().is_some());
+}
+
+#[test]
+fn test_serve() {
+    let mut world = World::new(Mode::Classic, 2);
+    world.set_serve_timeout(Some(100));
+    let stay = [PaddleMove::Stay; 2];
+    let down = [PaddleMove::Down, PaddleMove::Stay];
+    world.balls[0].set_motion(4, 20, -10, 0);
+    assert_eq!(world.step(&stay).unwrap(), [TeamName::Left]);
+    // the loser serves, the ball follows the paddle until the serve
+    assert!(world.is_serving(TeamName::Left));
+    let y = world.balls()[0].y();
+    world.step(&down).unwrap();
+    assert!(world.balls()[0].y() > y);
+    world.serve(TeamName::Left);
+    world.step(&down).unwrap();
+    assert!(!world.is_serving(TeamName::Left));
+    assert!(world.balls()[0].vx() > 0 && world.balls()[0].vy() > 0);
+
+    world.balls[0].set_motion(4, 20, -10, 0);
+    world.step(&stay).unwrap();
+    for _ in 0..100 {
+        world.step(&stay).unwrap();
+    }
+    assert!(world.balls()[0].vx() > 0 && world.balls()[0].vy() == 0);
+}
+
+#[test]
+fn test_spin() {
+    let mut world = World::new(Mode::Classic, 6);
+    world.set_spin(true);
+    // the left paddle moves down while hitting the ball, which then curves downwards
+    let paddle = world.paddles()[0].rect();
+    let x = paddle.right() + 2;
+    world.balls[0].set_motion(x, paddle.y() + paddle.height() as i32 / 2, -4, 0);
+    world.step(&[PaddleMove::Down, PaddleMove::Stay]).unwrap();
+    assert!(world.balls()[0].spin().1 > 0);
+    let vy = world.balls()[0].vy();
+    for _ in 0..20 {
+        world.step(&[PaddleMove::Stay; 2]).unwrap();
+    }
+    assert!(world.balls()[0].vy() > vy);
+
+    let mut writer = PacketWriter::new(0);
+    world.write(&mut writer);
+    let mut copy = World::new(Mode::Classic, 6);
+    copy.read(&mut PacketReader::new(&writer.bytes()[1..]))
+        .unwrap();
+    assert_eq!(copy.balls()[0], world.balls()[0]);
+}
+
+#[test]
+fn test_rally_speed() {
+    let mut world = World::new(Mode::Classic, 8);
+    world.set_rally_speed(RallySpeed {
+        per_hit: 10,
+        tiers: vec![(4, 20)],
+        cap: 160,
+    });
+    let stay = [PaddleMove::Stay; 2];
+    let paddle = world.paddles()[0].rect();
+    let y = paddle.y() + paddle.height() as i32 / 2;
+    for hits in 1..=6 {
+        world.balls[0].set_motion(paddle.right() + 2, y, -4, 0);
+        world.step(&stay).unwrap();
+        let percent = (100 + 10 * hits + if hits >= 4 { 20 } else { 0 }).min(160);
+        assert_eq!(world.balls()[0].vx_default(), BALL_VX * percent / 100);
+    }
+    // a goal starts a new rally
+    world.balls[0].set_motion(4, 20, -10, 0);
+    world.step(&stay).unwrap();
+    assert_eq!(world.balls()[0].vx_default(), BALL_VX);
+}
+
+#[test]
+fn test_squash() {
+    let mut world = World::new(Mode::Squash, 12);
+    assert_eq!(world.paddles().len(), 1);
+    let moves = [PaddleMove::Up, PaddleMove::Stay, PaddleMove::Down];
+    let mut best = 0;
+    let mut misses = 0;
+    for i in 0..20_000 {
+        for team in world.step(&[moves[i / 29 % 3]]).unwrap() {
+            assert_eq!(team, TeamName::Left);
+            misses += 1;
+        }
+        best = best.max(world.points(TeamName::Left));
+    }
+    assert!(best > 0 && misses > 0);
+    assert_eq!(world.winner(), None);
+}
+
+#[test]
+fn test_bricks() {
+    let mut world = World::new(Mode::Classic, 10);
+    world.set_bricks(Bricks::builtin("wall").unwrap());
+    let bricks = world.bricks().alive().count();
+    let moves = [PaddleMove::Up, PaddleMove::Stay, PaddleMove::Down];
+    let mut goals = 0;
+    for i in 0..1_500 {
+        goals += world
+            .step(&[moves[i / 23 % 3], moves[i / 31 % 3]])
+            .unwrap()
+            .len() as u32;
+    }
+    assert!(world.winner().is_none());
+    assert!(world.bricks().alive().count() < bricks);
+    let points = world.points(TeamName::Left) + world.points(TeamName::Right);
+    assert!(points > goals);
+
+    let mut writer = PacketWriter::new(0);
+    world.write(&mut writer);
+    let mut copy = World::new(Mode::Classic, 10);
+    copy.set_bricks(Bricks::builtin("wall").unwrap());
+    copy.read(&mut PacketReader::new(&writer.bytes()[1..]))
+        .unwrap();
+    assert_eq!(copy.bricks(), world.bricks());
+}
+
+#[test]
+fn test_predict() {
+    let mut world = World::new(Mode::Classic, 3);
+    let paddles: Vec<_> = world.active_paddles().collect();
+    let path = world.balls()[0].predict(&paddles, &world.court(), 90);
+    assert!(!world.balls()[0].walls(&paddles, &world.court()).is_empty());
+    for center in &path[1..] {
+        if !world.step(&[PaddleMove::Stay; 2]).unwrap().is_empty() {
+            break;
+        }
+        assert_eq!(world.balls()[0].center(), *center);
+    }
+}
+
+#[test]
+fn test_countdown() {
+    let mut world = World::new(Mode::Classic, 5);
+    world.set_countdown(30);
+    let ball = world.balls()[0].center();
+    let paddle = world.paddles()[0].rect();
+    for left in (0..30).rev() {
+        world.step(&[PaddleMove::Up, PaddleMove::Stay]).unwrap();
+        assert_eq!(world.countdown_left(), left);
+        assert_eq!(world.balls()[0].center(), ball);
+    }
+    assert_ne!(world.paddles()[0].rect(), paddle);
+    world.step(&[PaddleMove::Stay; 2]).unwrap();
+    assert_ne!(world.balls()[0].center(), ball);
+
+    // frozen again after a goal
+    while world.step(&[PaddleMove::Stay; 2]).unwrap().is_empty() {}
+    assert_eq!(world.countdown_left(), 30);
+    let ball = world.balls()[0].center();
+    world.step(&[PaddleMove::Stay; 2]).unwrap();
+    assert_eq!(world.balls()[0].center(), ball);
+
+    // resumed: the paddles move, the balls wait
+    world.freeze(60);
+    let paddle = world.paddles()[1].rect();
+    for _ in 0..60 {
+        world.step(&[PaddleMove::Stay, PaddleMove::Down]).unwrap();
+        assert_eq!(world.balls()[0].center(), ball);
+    }
+    assert_ne!(world.paddles()[1].rect(), paddle);
+    world.freeze(0);
+    assert_eq!(world.countdown_left(), 30);
+
+    let mut writer = PacketWriter::new(0);
+    world.write(&mut writer);
+    let mut copy = World::new(Mode::Classic, 5);
+    copy.set_countdown(30);
+    copy.read(&mut PacketReader::new(&writer.bytes()[1..]))
+        .unwrap();
+    assert_eq!(copy, world);
+
+    // a held serve and the effects wait with the balls
+    let mut world = World::new(Mode::Classic, 5);
+    world.set_power_ups(&[PowerUpKind::Grow]);
+    world.set_countdown(30);
+    world.hold_serve(0, TeamName::Left, 100);
+    world.power_ups.add(PowerUpKind::Grow, 0);
+    let effect = world.power_ups().effects()[0].ticks_left;
+    for _ in 0..30 {
+        world.step(&[PaddleMove::Down, PaddleMove::Stay]).unwrap();
+    }
+    assert_eq!(world.possession[0].held, Some((0, 100)));
+    assert_eq!(world.power_ups().effects()[0].ticks_left, effect);
+    world.step(&[PaddleMove::Stay; 2]).unwrap();
+    assert_eq!(world.possession[0].held, Some((0, 99)));
+
+    // the moving obstacles wait too, they can't push a frozen ball
+    let mut world = World::new(Mode::Classic, 5);
+    world.set_arena(Arena::builtin("movers").unwrap());
+    world.set_countdown(30);
+    let obstacles = world.arena().obstacles(world.tick());
+    let ball = world.balls()[0].rect();
+    for _ in 0..30 {
+        world.step(&[PaddleMove::Up, PaddleMove::Down]).unwrap();
+        assert_eq!(world.arena().obstacles(world.tick()), obstacles);
+        assert_eq!(world.balls()[0].rect(), ball);
+    }
+    world.step(&[PaddleMove::Stay; 2]).unwrap();
+    assert_ne!(world.arena().obstacles(world.tick()), obstacles);
+}